@@ -0,0 +1,115 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, ItemFn, LitStr, Token};
+
+pub(crate) struct Args {
+    message: Option<LitStr>,
+    args: Vec<Arg>,
+}
+
+enum Arg {
+    Context(Ident),
+    Debug(Ident),
+    Display(Ident),
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let message = if input.peek(LitStr) {
+            let message = input.parse()?;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+            Some(message)
+        } else {
+            None
+        };
+        let args = Punctuated::<Arg, Token![,]>::parse_terminated(input)?
+            .into_iter()
+            .collect();
+        Ok(Args { message, args })
+    }
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.parse::<Option<Token![?]>>()?.is_some() {
+            Ok(Arg::Debug(input.parse()?))
+        } else if input.parse::<Option<Token![%]>>()?.is_some() {
+            Ok(Arg::Display(input.parse()?))
+        } else {
+            Ok(Arg::Context(input.parse()?))
+        }
+    }
+}
+
+pub(crate) fn expand(args: Args, mut item: ItemFn) -> syn::Result<TokenStream> {
+    if let syn::ReturnType::Default = item.sig.output {
+        return Err(syn::Error::new_spanned(
+            &item.sig,
+            "anyhow_context requires a function returning anyhow::Result",
+        ));
+    }
+
+    let message = match args.message {
+        Some(message) => message.value(),
+        None => format!("while calling {}", item.sig.ident),
+    };
+    let renders = args.args.iter().map(|arg| {
+        let (ident, render) = match arg {
+            Arg::Context(ident) => (
+                ident,
+                quote! { ::anyhow_std::ContextDisplay::context_display(&#ident) },
+            ),
+            Arg::Debug(ident) => (
+                ident,
+                quote! { ::anyhow_std::display::truncate_debug(&#ident) },
+            ),
+            Arg::Display(ident) => (
+                ident,
+                quote! { ::anyhow_std::display::truncate_display(&#ident) },
+            ),
+        };
+        let name = ident.to_string();
+        quote! { format!("{} {}", #name, #render) }
+    });
+    let context = if args.args.is_empty() {
+        quote! { #message.to_string() }
+    } else {
+        quote! {
+            format!("{} with {}", #message, [#( #renders ),*].join(", "))
+        }
+    };
+
+    // The body runs in a closure or async block passed through a helper, which fixes its output
+    // as `anyhow::Result<_>` for `?` without naming the return type, so `impl Trait` works:
+    let block = &item.block;
+    let result = if item.sig.asyncness.is_some() {
+        quote! {{
+            fn __anyhow_std_body<T, F>(body: F) -> F
+            where
+                F: ::std::future::Future<Output = ::anyhow_std::__anyhow::Result<T>>,
+            {
+                body
+            }
+            __anyhow_std_body(async #block).await
+        }}
+    } else {
+        quote! {{
+            fn __anyhow_std_body<T, F>(body: F) -> ::anyhow_std::__anyhow::Result<T>
+            where
+                F: ::std::ops::FnOnce() -> ::anyhow_std::__anyhow::Result<T>,
+            {
+                body()
+            }
+            __anyhow_std_body(|| #block)
+        }}
+    };
+
+    item.block = syn::parse_quote! {{
+        ::anyhow_std::__anyhow::Context::with_context(#result, || #context)
+    }};
+    Ok(quote! { #item })
+}
@@ -0,0 +1,144 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, LitStr, Type};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let struct_prefix = parse_struct_attrs(&input)?;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "FromEnv requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FromEnv requires a struct with named fields",
+            ))
+        }
+    };
+
+    let mut idents = vec![];
+    let mut lookups = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have idents");
+        let attrs = parse_field_attrs(field)?;
+        let key = attrs
+            .key
+            .unwrap_or_else(|| ident.unraw().to_string().to_uppercase());
+        let key = format!("{}{}", struct_prefix, key);
+        let ty = &field.ty;
+
+        let lookup = if let Some(inner) = option_inner_type(ty) {
+            if attrs.default.is_some() {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`default` cannot be combined with an `Option` field",
+                ));
+            }
+            if is_bool(inner) {
+                quote! { reader.var_bool_opt(format!("{}{}", prefix, #key)) }
+            } else {
+                quote! { reader.var_parse_opt::<#inner, _>(format!("{}{}", prefix, #key)) }
+            }
+        } else if let Some(default) = attrs.default {
+            if is_bool(ty) {
+                quote! { reader.var_bool_or(format!("{}{}", prefix, #key), #default) }
+            } else {
+                quote! { reader.var_parse_or::<#ty, _>(format!("{}{}", prefix, #key), #default) }
+            }
+        } else if is_bool(ty) {
+            quote! { reader.var_bool(format!("{}{}", prefix, #key)) }
+        } else {
+            quote! { reader.var_parse::<#ty, _>(format!("{}{}", prefix, #key)) }
+        };
+
+        lookups.push(quote! { let #ident = #lookup; });
+        idents.push(ident);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::anyhow_std::env::FromEnv for #name #ty_generics #where_clause {
+            fn from_env_with_prefix(prefix: &str) -> ::anyhow_std::__anyhow::Result<Self> {
+                let mut reader = ::anyhow_std::env::Reader::new();
+                #( #lookups )*
+                ::anyhow_std::__anyhow::Context::with_context(reader.finish(), || {
+                    format!("while loading {} from the environment", stringify!(#name))
+                })?;
+                Ok(Self {
+                    #( #idents: #idents.expect("Reader::finish reports every failed lookup"), )*
+                })
+            }
+        }
+    })
+}
+
+fn parse_struct_attrs(input: &DeriveInput) -> syn::Result<String> {
+    let mut prefix = String::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("from_env")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                prefix = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported from_env struct attribute"))
+            }
+        })?;
+    }
+    Ok(prefix)
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    key: Option<String>,
+    default: Option<LitStr>,
+}
+
+fn parse_field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("from_env")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                attrs.key = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported from_env field attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Recognize `Option<T>` by its final path segment, returning `T`
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(typath) = ty else {
+        return None;
+    };
+    let segment = typath.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Recognize `bool`, which is parsed with the spellings of `env::var_bool` rather than [FromStr](std::str::FromStr)
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(typath) if typath.qself.is_none() && typath.path.is_ident("bool"))
+}
@@ -0,0 +1,62 @@
+//! Procedural macros for [anyhow-std](https://docs.rs/anyhow-std)
+//!
+//! These are re-exported by `anyhow-std` behind cargo features, and should be used from there.
+
+mod context;
+mod fromenv;
+
+use proc_macro::TokenStream;
+
+/// Derive `anyhow_std::env::FromEnv` for a struct with named fields
+#[proc_macro_derive(FromEnv, attributes(from_env))]
+pub fn derive_from_env(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    fromenv::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Attach a context describing selected arguments to the error of a function returning `anyhow::Result`
+///
+/// The attribute takes an optional message, which defaults to `while calling <function name>`,
+/// followed by the arguments to describe. Each argument is rendered with
+/// `anyhow_std::ContextDisplay` by default, so paths, os strings, and strings follow the
+/// `anyhow_std::display::DisplayPolicy` truncation rules. An argument prefixed with `?` is
+/// rendered with `Debug`, and with `%` is rendered with `Display`, both also truncated:
+///
+/// ```
+/// use anyhow_std::{anyhow_context, PathAnyhow};
+/// use std::path::Path;
+///
+/// #[anyhow_context("while loading config", path, uid)]
+/// fn load_config(path: &Path, uid: u32) -> anyhow::Result<String> {
+///     path.read_to_string_anyhow()
+/// }
+///
+/// #[anyhow_context(name, ?flags)]
+/// fn check(name: &str, flags: &[&str]) -> anyhow::Result<()> {
+///     anyhow::ensure!(!flags.is_empty(), "no flags");
+///     Ok(())
+/// }
+///
+/// assert_eq!(
+///     format!("{:#}", load_config(Path::new("/this/path/should/not/exist"), 1000).err().unwrap()),
+///     r#"while loading config with path "/this/path/should/not/exist", uid 1000: while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+/// );
+/// assert_eq!(
+///     format!("{:#}", check("lint", &[]).err().unwrap()),
+///     r#"while calling check with name "lint", flags []: no flags"#,
+/// );
+/// ```
+///
+/// The context is only rendered when the function fails, so the described arguments are
+/// borrowed after the body runs, and the body must not move them. `async fn` and `impl Trait`
+/// return types are supported.
+#[proc_macro_attribute]
+pub fn anyhow_context(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as context::Args);
+    let item = syn::parse_macro_input!(item as syn::ItemFn);
+    context::expand(args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,144 @@
+use anyhow_std::anyhow_context;
+use anyhow_std::display::DisplayPolicy;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+#[anyhow_context(path, uid)]
+fn load(path: &Path, uid: u32) -> anyhow::Result<()> {
+    anyhow::bail!("{} cannot read {}", uid, path.display())
+}
+
+#[anyhow_context("while consuming", name, ?tags, %count)]
+fn consume(name: OsString, tags: Vec<&str>, count: usize) -> anyhow::Result<usize> {
+    if tags.is_empty() {
+        return Err(anyhow::anyhow!("no tags"));
+    }
+    Ok(tags.len() + count + name.len())
+}
+
+#[anyhow_context(n)]
+fn evens(n: u32) -> anyhow::Result<impl Iterator<Item = u32>> {
+    if n == 0 {
+        return Err(anyhow::anyhow!("empty range"));
+    }
+    let file = std::fs::File::open("/")?;
+    drop(file);
+    Ok((0..n).filter(|i| i % 2 == 0))
+}
+
+struct Counted;
+
+static RENDERS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+impl std::fmt::Display for Counted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        RENDERS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        f.write_str("counted")
+    }
+}
+
+#[anyhow_context(%counted)]
+fn render_on_failure(counted: Counted, fail: bool) -> anyhow::Result<()> {
+    anyhow::ensure!(!fail, "failed");
+    Ok(())
+}
+
+#[anyhow_context("while waiting")]
+async fn wait(fail: bool) -> anyhow::Result<()> {
+    drop(std::fs::File::open("/")?);
+    anyhow::ensure!(!fail, "timed out");
+    Ok(())
+}
+
+struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    #[anyhow_context(key)]
+    fn get(&self, key: &str) -> anyhow::Result<String> {
+        anyhow::bail!("missing under {}", self.root.display())
+    }
+}
+
+#[test]
+fn sync_fn() {
+    assert_eq!(
+        format!("{:#}", load(Path::new("/etc/shadow"), 1000).err().unwrap()),
+        r#"while calling load with path "/etc/shadow", uid 1000: 1000 cannot read /etc/shadow"#,
+    );
+}
+
+#[test]
+fn early_return() {
+    assert_eq!(consume(OsString::from("x"), vec!["a", "b"], 1).unwrap(), 4);
+    assert_eq!(
+        format!(
+            "{:#}",
+            consume(OsString::from("x"), vec![], 7).err().unwrap()
+        ),
+        r#"while consuming with name "x", tags [], count 7: no tags"#,
+    );
+}
+
+#[test]
+fn method() {
+    let store = Store {
+        root: PathBuf::from("/var/store"),
+    };
+    assert_eq!(
+        format!("{:#}", store.get("k").err().unwrap()),
+        r#"while calling get with key "k": missing under /var/store"#,
+    );
+}
+
+#[test]
+fn truncated() {
+    let policy = DisplayPolicy {
+        max_len: 10,
+        head_len: 4,
+        ..DisplayPolicy::default()
+    };
+    let e = policy.with(|| load(Path::new("/a/very/long/path"), 0).err().unwrap());
+    assert_eq!(
+        format!("{:#}", e),
+        "while calling load with path \"/a/v\u{2772}\u{2026}\u{2773}ath\", uid 0: 0 cannot read /a/very/long/path",
+    );
+}
+
+#[test]
+fn async_fn() {
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+
+    let mut fut = std::pin::pin!(wait(true));
+    let Poll::Ready(res) = fut.as_mut().poll(&mut Context::from_waker(Waker::noop())) else {
+        panic!("wait should complete immediately");
+    };
+    assert_eq!(
+        format!("{:#}", res.err().unwrap()),
+        "while waiting: timed out"
+    );
+}
+
+#[test]
+fn impl_trait_output() {
+    assert_eq!(evens(5).unwrap().collect::<Vec<_>>(), vec![0, 2, 4]);
+    assert_eq!(
+        format!("{:#}", evens(0).err().unwrap()),
+        "while calling evens with n 0: empty range"
+    );
+}
+
+#[test]
+fn context_rendered_only_on_failure() {
+    use std::sync::atomic::Ordering::SeqCst;
+
+    render_on_failure(Counted, false).unwrap();
+    assert_eq!(RENDERS.load(SeqCst), 0);
+    assert_eq!(
+        format!("{:#}", render_on_failure(Counted, true).err().unwrap()),
+        "while calling render_on_failure with counted counted: failed"
+    );
+    assert_eq!(RENDERS.load(SeqCst), 1);
+}
@@ -0,0 +1,63 @@
+use anyhow_std::env::FromEnv;
+
+#[derive(Debug, PartialEq, FromEnv)]
+#[from_env(prefix = "ANYHOW_STD_MACROS_TEST_")]
+struct Config {
+    host: String,
+    #[from_env(default = "8080")]
+    port: u16,
+    #[from_env(key = "VERBOSE")]
+    debug: Option<bool>,
+}
+
+#[test]
+fn from_env_ok() -> anyhow::Result<()> {
+    std::env::set_var("OK_ANYHOW_STD_MACROS_TEST_HOST", "localhost");
+    std::env::set_var("OK_ANYHOW_STD_MACROS_TEST_VERBOSE", "on");
+
+    assert_eq!(
+        Config::from_env_with_prefix("OK_")?,
+        Config {
+            host: "localhost".to_string(),
+            port: 8080,
+            debug: Some(true),
+        },
+    );
+    Ok(())
+}
+
+#[test]
+fn from_env_reports_all_fields() {
+    std::env::set_var("ERR_ANYHOW_STD_MACROS_TEST_PORT", "http");
+    std::env::set_var("ERR_ANYHOW_STD_MACROS_TEST_VERBOSE", "loud");
+
+    assert_eq!(
+        format!("{:#}", Config::from_env_with_prefix("ERR_").err().unwrap()),
+        r#"while loading Config from the environment: 3 environment variable errors:
+- "ERR_ANYHOW_STD_MACROS_TEST_HOST": environment variable not found
+- "ERR_ANYHOW_STD_MACROS_TEST_PORT": while parsing "http" as u16: invalid digit found in string
+- "ERR_ANYHOW_STD_MACROS_TEST_VERBOSE": while parsing "loud" as bool: expected one of 1, true, yes, on, 0, false, no, off"#,
+    );
+}
+
+#[derive(Debug, PartialEq, FromEnv)]
+#[from_env(prefix = "ANYHOW_STD_MACROS_TEST_RAW_")]
+struct RawIdents {
+    r#type: String,
+    #[from_env(default = "yes")]
+    r#async: bool,
+}
+
+#[test]
+fn from_env_raw_idents_and_bool_default() -> anyhow::Result<()> {
+    std::env::set_var("ANYHOW_STD_MACROS_TEST_RAW_TYPE", "plain");
+
+    assert_eq!(
+        RawIdents::from_env()?,
+        RawIdents {
+            r#type: "plain".to_string(),
+            r#async: true,
+        },
+    );
+    Ok(())
+}
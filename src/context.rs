@@ -0,0 +1,256 @@
+//! Error context conventions shared by this crate's wrappers
+//!
+//! Contexts may also be supplied for a whole region of code with [scope], or for an async task
+//! with [scoped].
+use crate::display::path_debug;
+use crate::display::truncate;
+use crate::error::{CommandContext, PathContext};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod scope;
+
+pub(crate) use self::scope::attach as attach_scopes;
+pub use self::scope::{scope, scoped, Frame, Scope, Scoped};
+
+/// Describe a value for error contexts
+///
+/// This is how [wrap_method](crate::wrap_method) describes the receiver of the methods it
+/// generates, so implementing it for a third-party type gives its extension methods a
+/// consistent description.
+pub trait AnyhowContext {
+    /// Describe `self` for error contexts
+    fn anyhow_context(&self) -> String;
+
+    /// Attach the description of `self` to `error`
+    ///
+    /// Implementations may override this to attach a typed context value, which can be
+    /// recovered with [anyhow::Error::downcast_ref], as the [crate::error] helpers do.
+    fn attach_anyhow_context(&self, error: anyhow::Error) -> anyhow::Error {
+        error.context(self.anyhow_context())
+    }
+}
+
+impl AnyhowContext for Path {
+    fn anyhow_context(&self) -> String {
+        format!("while processing path {}", path_debug(self))
+    }
+
+    fn attach_anyhow_context(&self, error: anyhow::Error) -> anyhow::Error {
+        PathContext::attach(error, "while processing path".into(), self)
+    }
+}
+
+impl AnyhowContext for OsStr {
+    fn anyhow_context(&self) -> String {
+        format!(
+            "while processing os string {:?}",
+            truncate(self.to_string_lossy())
+        )
+    }
+}
+
+impl AnyhowContext for str {
+    fn anyhow_context(&self) -> String {
+        format!("while processing string {:?}", truncate(self.into()))
+    }
+}
+
+/// Render a value for error contexts, such as the arguments described by `anyhow_context`
+///
+/// Paths, os strings, and strings are quoted and truncated like other contexts in this crate,
+/// whereas numbers and other scalars are displayed as-is.
+pub trait ContextDisplay {
+    /// Render `self` for error contexts
+    fn context_display(&self) -> String;
+}
+
+impl ContextDisplay for Path {
+    fn context_display(&self) -> String {
+        path_debug(self)
+    }
+}
+
+impl ContextDisplay for PathBuf {
+    fn context_display(&self) -> String {
+        path_debug(self)
+    }
+}
+
+impl ContextDisplay for OsStr {
+    fn context_display(&self) -> String {
+        format!("{:?}", truncate(self.to_string_lossy()))
+    }
+}
+
+impl ContextDisplay for OsString {
+    fn context_display(&self) -> String {
+        self.as_os_str().context_display()
+    }
+}
+
+impl ContextDisplay for str {
+    fn context_display(&self) -> String {
+        format!("{:?}", truncate(self.into()))
+    }
+}
+
+impl ContextDisplay for String {
+    fn context_display(&self) -> String {
+        self.as_str().context_display()
+    }
+}
+
+impl ContextDisplay for Command {
+    fn context_display(&self) -> String {
+        crate::display::command_debug(self)
+    }
+}
+
+impl<T> ContextDisplay for Option<T>
+where
+    T: ContextDisplay,
+{
+    fn context_display(&self) -> String {
+        match self {
+            Some(v) => v.context_display(),
+            None => "None".to_string(),
+        }
+    }
+}
+
+impl<T> ContextDisplay for &T
+where
+    T: ContextDisplay + ?Sized,
+{
+    fn context_display(&self) -> String {
+        (**self).context_display()
+    }
+}
+
+impl<T> ContextDisplay for &mut T
+where
+    T: ContextDisplay + ?Sized,
+{
+    fn context_display(&self) -> String {
+        (**self).context_display()
+    }
+}
+
+impl<T> ContextDisplay for Box<T>
+where
+    T: ContextDisplay + ?Sized,
+{
+    fn context_display(&self) -> String {
+        (**self).context_display()
+    }
+}
+
+macro_rules! context_display_via_display {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl ContextDisplay for $t {
+                fn context_display(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+context_display_via_display!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+/// Extend [Result] with the error contexts used throughout this crate
+///
+/// This gives errors from other APIs taking paths or commands the same descriptions as
+/// [PathAnyhow](crate::PathAnyhow) and [CommandAnyhow](crate::CommandAnyhow) errors:
+///
+/// ```
+/// use anyhow_std::ResultAnyhow;
+/// use std::path::Path;
+///
+/// let path = Path::new("/this/path/should/not/exist");
+/// let e = std::fs::read(path).with_path_context(path).err().unwrap();
+/// assert_eq!(
+///     format!("{:#}", e),
+///     r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+/// );
+/// ```
+pub trait ResultAnyhow<T> {
+    /// Provide `path` as error context, as [PathAnyhow](crate::PathAnyhow) methods do
+    fn with_path_context<P>(self, path: P) -> anyhow::Result<T>
+    where
+        P: AsRef<Path>;
+
+    /// Provide a second path `arg` named by `argname` as error context, as [PathAnyhow](crate::PathAnyhow) methods taking two paths do
+    fn with_path_arg_context<Q>(self, argname: &str, arg: Q) -> anyhow::Result<T>
+    where
+        Q: AsRef<Path>;
+
+    /// Provide `path` and a second path `arg` named by `argname` as error context, as [PathAnyhow](crate::PathAnyhow) methods taking two paths do
+    fn with_path_pair_context<P, Q>(self, argname: &str, path: P, arg: Q) -> anyhow::Result<T>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>;
+
+    /// Provide `cmd` as error context, as [CommandAnyhow](crate::CommandAnyhow) methods do
+    fn with_command_context(self, cmd: &Command) -> anyhow::Result<T>;
+
+    /// Provide the frames of any active [scope] as error context
+    ///
+    /// This is applied by the other methods of this trait, so it is only needed for errors
+    /// given some other context.
+    fn with_scope_context(self) -> anyhow::Result<T>;
+}
+
+impl<T, E> ResultAnyhow<T> for Result<T, E>
+where
+    E: Into<anyhow::Error>,
+{
+    fn with_path_context<P>(self, path: P) -> anyhow::Result<T>
+    where
+        P: AsRef<Path>,
+    {
+        self.map_err(|e| path.as_ref().attach_anyhow_context(e.into()))
+            .with_scope_context()
+    }
+
+    fn with_path_arg_context<Q>(self, argname: &str, arg: Q) -> anyhow::Result<T>
+    where
+        Q: AsRef<Path>,
+    {
+        self.map_err(|e| {
+            PathContext::attach(e.into(), format!("with {}", argname).into(), arg.as_ref())
+        })
+        .with_scope_context()
+    }
+
+    fn with_path_pair_context<P, Q>(self, argname: &str, path: P, arg: Q) -> anyhow::Result<T>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        // Frames are attached once, outside both paths:
+        self.map_err(|e| {
+            PathContext::attach(e.into(), format!("with {}", argname).into(), arg.as_ref())
+        })
+        .with_path_context(path)
+    }
+
+    fn with_command_context(self, cmd: &Command) -> anyhow::Result<T> {
+        self.map_err(|e| {
+            CommandContext::attach(e.into(), cmd, crate::CommandAnyhow::anyhow_context(cmd))
+        })
+        .with_scope_context()
+    }
+
+    fn with_scope_context(self) -> anyhow::Result<T> {
+        self.map_err(|e| attach_scopes(e.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,167 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+thread_local! {
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A description pushed by [scope], attached as context to errors from this crate's wrappers
+///
+/// Frames are attached as this type, so they can be found with [anyhow::Error::downcast_ref].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    description: String,
+    // Identifies the scope, so it is attached once however many wrappers an error passes through:
+    id: u64,
+    // The ids of this and every frame attached beneath it:
+    attached: Vec<u64>,
+}
+
+impl Frame {
+    fn new(description: String) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        Frame {
+            description,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            attached: vec![],
+        }
+    }
+
+    /// The frame description
+    pub fn as_str(&self) -> &str {
+        &self.description
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.description.fmt(f)
+    }
+}
+
+/// Push `frame` onto this thread's context stack until the returned guard is dropped
+///
+/// While the guard is alive, errors from [PathAnyhow](crate::PathAnyhow),
+/// [CommandAnyhow](crate::CommandAnyhow), the other extension traits, the context methods of
+/// [ResultAnyhow](crate::ResultAnyhow), the [env](crate::env) and [process](crate::process)
+/// functions, and the wrapper types of [fs](crate::fs) and [process](crate::process) are given
+/// each active frame as context, outermost first:
+///
+/// ```
+/// use anyhow_std::context::scope;
+/// use anyhow_std::PathAnyhow;
+/// use std::path::Path;
+///
+/// let _build = scope("while building package foo");
+/// let _step = scope("while generating docs");
+/// let e = Path::new("/this/path/should/not/exist").read_anyhow().err().unwrap();
+/// assert_eq!(
+///     format!("{:#}", e),
+///     r#"while building package foo: while generating docs: while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+/// );
+/// ```
+///
+/// The stack is thread-local, so the guard cannot be sent to another thread; for async tasks
+/// which may move between threads, use [scoped] instead.
+#[must_use = "the frame is popped when the guard is dropped"]
+pub fn scope<S>(frame: S) -> Scope
+where
+    S: Into<String>,
+{
+    Scope::push(Frame::new(frame.into()))
+}
+
+/// Attach `frame` to errors from this crate's wrappers whenever `future` is polled, as with [scope]
+pub fn scoped<S, F>(frame: S, future: F) -> Scoped<F>
+where
+    S: Into<String>,
+    F: Future,
+{
+    Scoped {
+        frame: Frame::new(frame.into()),
+        future,
+    }
+}
+
+/// The guard returned by [scope], which pops its frame on drop
+#[derive(Debug)]
+pub struct Scope {
+    depth: usize,
+    // The frame belongs to this thread's stack:
+    not_send: PhantomData<*const ()>,
+}
+
+impl Scope {
+    fn push(frame: Frame) -> Self {
+        let depth = FRAMES.with(|frames| {
+            let mut frames = frames.borrow_mut();
+            frames.push(frame);
+            frames.len() - 1
+        });
+        Scope {
+            depth,
+            not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        // Truncating also pops any inner frames whose guards were leaked or dropped out of order:
+        FRAMES.with(|frames| frames.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// The future returned by [scoped]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Scoped<F> {
+    frame: Frame,
+    future: F,
+}
+
+impl<F> Future for Scoped<F>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned; it is never moved out of `self`:
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        let _scope = Scope::push(this.frame.clone());
+        future.poll(cx)
+    }
+}
+
+/// Attach the active frames to `error`, except those already attached by an inner wrapper
+///
+/// Frames of scopes entered after the error was first given frames, such as an outer scope
+/// it propagates into, are added outside the existing ones.
+pub(crate) fn attach(error: anyhow::Error) -> anyhow::Error {
+    let mut attached = error
+        .downcast_ref::<Frame>()
+        .map(|f| f.attached.clone())
+        .unwrap_or_default();
+    let pending: Vec<Frame> = FRAMES.with(|frames| {
+        frames
+            .borrow()
+            .iter()
+            .rev()
+            .filter(|f| !attached.contains(&f.id))
+            .cloned()
+            .collect()
+    });
+    pending.into_iter().fold(error, |error, mut frame| {
+        attached.push(frame.id);
+        frame.attached = attached.clone();
+        error.context(frame)
+    })
+}
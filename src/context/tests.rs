@@ -0,0 +1,225 @@
+use crate::testutils::{err_str, stringify_error};
+use crate::ResultAnyhow;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::process::Command;
+
+fn not_found() -> std::io::Result<()> {
+    Err(Error::from(ErrorKind::NotFound))
+}
+
+#[test]
+fn path_context_matches_path_anyhow() {
+    use crate::PathAnyhow;
+
+    let path = Path::new("/this/path/should/not/exist");
+    assert_eq!(
+        stringify_error(std::fs::read(path).with_path_context(path)),
+        stringify_error(path.read_anyhow()),
+    );
+}
+
+#[test]
+fn path_pair_context() {
+    assert_eq!(
+        stringify_error(not_found().with_path_pair_context("to", "/src", "/dst")),
+        err_str(r#"while processing path "/src": with to "/dst": entity not found"#),
+    );
+}
+
+#[test]
+fn command_context() {
+    let cmd = Command::new("frob");
+    assert_eq!(
+        stringify_error(not_found().with_command_context(&cmd)),
+        err_str(r#"command: "frob": entity not found"#),
+    );
+}
+
+#[test]
+fn anyhow_result() {
+    let res: anyhow::Result<()> = Err(anyhow::anyhow!("failed"));
+    assert_eq!(
+        stringify_error(res.with_path_context("/foo")),
+        err_str(r#"while processing path "/foo": failed"#),
+    );
+}
+
+#[test]
+fn context_display() {
+    use crate::ContextDisplay;
+    use std::ffi::OsString;
+
+    assert_eq!(Path::new("/etc").context_display(), r#""/etc""#);
+    assert_eq!(OsString::from("x y").context_display(), r#""x y""#);
+    assert_eq!("tab\t".context_display(), r#""tab\t""#);
+    assert_eq!(Some(42_u16).context_display(), "42");
+    assert_eq!(None::<bool>.context_display(), "None");
+    assert_eq!(Command::new("ls").context_display(), r#""ls""#);
+}
+
+#[test]
+fn scope_nesting() {
+    use crate::context::scope;
+    use crate::PathAnyhow;
+
+    let path = Path::new("/this/path/should/not/exist");
+    let outer = scope("while building package foo");
+    {
+        let _inner = scope("while generating docs");
+        assert_eq!(
+            stringify_error(path.read_anyhow()),
+            err_str(
+                r#"while building package foo: while generating docs: while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+            ),
+        );
+    }
+    assert_eq!(
+        stringify_error(path.read_anyhow()),
+        err_str(
+            r#"while building package foo: while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+        ),
+    );
+    drop(outer);
+    assert_eq!(
+        stringify_error(path.read_anyhow()),
+        err_str(
+            r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+        ),
+    );
+}
+
+#[test]
+fn scope_attached_once() {
+    use crate::context::{scope, Frame};
+    use crate::process::CommandBuilder;
+
+    let _scope = scope("while testing");
+    let e = CommandBuilder::new("true")
+        .stdout_to_path_anyhow("/this/path/should/not/exist")
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", e),
+        r#"while redirecting stdout of command: "true": while testing: while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#,
+    );
+    assert_eq!(
+        e.downcast_ref::<Frame>().map(Frame::as_str),
+        Some("while testing")
+    );
+}
+
+#[test]
+fn scope_entered_later_is_added() {
+    use crate::context::scope;
+
+    let res = {
+        let _inner = scope("while running step");
+        not_found().with_path_context("/foo")
+    };
+    let _outer = scope("while building package foo");
+    assert_eq!(
+        stringify_error(res.with_scope_context().with_scope_context()),
+        err_str(
+            r#"while building package foo: while running step: while processing path "/foo": entity not found"#
+        ),
+    );
+}
+
+#[test]
+fn scope_applies_to_all_wrappers() {
+    use crate::context::scope;
+    use crate::{OsStrAnyhow, StrAnyhow, TryIntoAnyhow};
+    use std::ffi::OsStr;
+
+    let _scope = scope("while testing");
+    assert_eq!(
+        stringify_error("x".parse_anyhow::<u8>()),
+        err_str(r#"while testing: while parsing "x" as u8: invalid digit found in string"#),
+    );
+    assert_eq!(
+        stringify_error(OsStr::new("").to_path_anyhow()),
+        err_str(r#"while testing: while processing os string "": empty path"#),
+    );
+    assert_eq!(
+        stringify_error(300_u32.try_into_anyhow::<u8>()),
+        err_str("while testing: while converting 300 from u32 to u8: out of range integral type conversion attempted"),
+    );
+    assert_eq!(
+        stringify_error(crate::env::var("ANYHOW_STD_TEST_SCOPE_UNSET")),
+        err_str(
+            r#"while testing: environment variable "ANYHOW_STD_TEST_SCOPE_UNSET": environment variable not found"#
+        ),
+    );
+    assert_eq!(
+        stringify_error(crate::env::ScopedVar::set("", "x").map(drop)),
+        err_str(r#"while testing: environment variable "": environment variable name is empty"#),
+    );
+    assert_eq!(
+        stringify_error(crate::process::command_from_shell_str("").map(drop)),
+        err_str(r#"while testing: while parsing command line "": empty command line"#),
+    );
+    assert_eq!(
+        stringify_error(not_found().with_path_arg_context("dest", "/bar")),
+        err_str(r#"while testing: with dest "/bar": entity not found"#),
+    );
+    assert_eq!(
+        stringify_error(not_found().with_path_pair_context("dest", "/foo", "/bar")),
+        err_str(
+            r#"while testing: while processing path "/foo": with dest "/bar": entity not found"#
+        ),
+    );
+}
+
+#[test]
+fn scope_is_thread_local() {
+    use crate::context::scope;
+
+    let _scope = scope("while on the main thread");
+    let res = std::thread::spawn(|| {
+        stringify_error(not_found().with_command_context(&Command::new("frob")))
+    })
+    .join()
+    .unwrap();
+    assert_eq!(res, err_str(r#"command: "frob": entity not found"#));
+}
+
+#[test]
+fn scoped_future() {
+    use crate::context::scoped;
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+
+    let fut = scoped("while running task", async {
+        not_found().with_path_context("/foo")
+    });
+    let mut fut = std::pin::pin!(fut);
+    let Poll::Ready(res) = fut.as_mut().poll(&mut Context::from_waker(Waker::noop())) else {
+        panic!("future should complete immediately");
+    };
+    assert_eq!(
+        stringify_error(res),
+        err_str(r#"while running task: while processing path "/foo": entity not found"#),
+    );
+    assert_eq!(
+        stringify_error(not_found().with_path_context("/foo")),
+        err_str(r#"while processing path "/foo": entity not found"#),
+    );
+}
+
+#[test]
+fn scope_attached_once_beneath_path_context() {
+    use crate::context::scope;
+
+    let _scope = scope("while testing");
+    assert_eq!(
+        stringify_error(
+            not_found()
+                .with_path_context("/foo")
+                .with_path_context("/bar")
+        ),
+        err_str(
+            r#"while processing path "/bar": while testing: while processing path "/foo": entity not found"#
+        ),
+    );
+}
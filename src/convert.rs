@@ -0,0 +1,50 @@
+use crate::ResultAnyhow;
+use anyhow::Context;
+use std::fmt::Debug;
+
+/// Extend the primitive integer types with [TryInto] conversions which describe the value as error context
+///
+/// Comparisons of [SystemTime](std::time::SystemTime) values are described by
+/// [SystemTimeAnyhow](crate::SystemTimeAnyhow) instead.
+///
+/// Unlike a bare [std::num::TryFromIntError], the error gives the value and both types:
+///
+/// ```
+/// use anyhow_std::TryIntoAnyhow;
+///
+/// let e = 300_u64.try_into_anyhow::<u8>().err().unwrap();
+/// assert_eq!(
+///     format!("{:#}", e),
+///     "while converting 300 from u64 to u8: out of range integral type conversion attempted",
+/// );
+/// ```
+pub trait TryIntoAnyhow: Copy + Debug {
+    /// Wrap [TryInto::try_into], providing the value, source type, and target type as error context
+    fn try_into_anyhow<T>(self) -> anyhow::Result<T>
+    where
+        Self: TryInto<T>,
+        <Self as TryInto<T>>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.try_into()
+            .with_context(|| {
+                format!(
+                    "while converting {:?} from {} to {}",
+                    self,
+                    std::any::type_name::<Self>(),
+                    std::any::type_name::<T>()
+                )
+            })
+            .with_scope_context()
+    }
+}
+
+macro_rules! impl_try_into_anyhow {
+    ( $( $t:ty ),* ) => {
+        $( impl TryIntoAnyhow for $t {} )*
+    };
+}
+
+impl_try_into_anyhow!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[cfg(test)]
+mod tests;
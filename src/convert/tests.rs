@@ -0,0 +1,25 @@
+use crate::testutils::{err_str, stringify_error};
+use crate::TryIntoAnyhow;
+use test_case::test_case;
+
+#[test_case(4096 => Ok(4096))]
+#[test_case(1 << 40 => err_str("while converting 1099511627776 from u64 to u32: out of range integral type conversion attempted"))]
+fn u64_to_u32(input: u64) -> Result<u32, String> {
+    stringify_error(input.try_into_anyhow())
+}
+
+#[test_case(1_700_000_000 => Ok(1_700_000_000))]
+#[test_case(-1 => err_str("while converting -1 from i64 to u32: out of range integral type conversion attempted"))]
+fn i64_to_u32(input: i64) -> Result<u32, String> {
+    stringify_error(input.try_into_anyhow())
+}
+
+#[test]
+fn char_from_u32() {
+    assert_eq!(
+        stringify_error(0xD800_u32.try_into_anyhow::<char>()),
+        err_str(
+            "while converting 55296 from u32 to char: converted integer out of range for `char`"
+        )
+    );
+}
@@ -0,0 +1,273 @@
+//! Control how long strings, paths, and commands are displayed in error contexts
+//!
+//! Error contexts embed os strings, paths, and command lines, which may be arbitrarily long. A
+//! [DisplayPolicy] truncates them by keeping a head and a tail around a `❲…❳` marker. The policy
+//! is process-wide, set with [DisplayPolicy::set_global], and may be overridden within a
+//! thread for the duration of a call with [DisplayPolicy::with]:
+//!
+//! ```
+//! use anyhow_std::display::DisplayPolicy;
+//! use anyhow_std::OsStrAnyhow;
+//! use std::ffi::OsStr;
+//!
+//! let policy = DisplayPolicy {
+//!     max_len: 12,
+//!     head_len: 4,
+//!     ..DisplayPolicy::default()
+//! };
+//! let e = policy.with(|| OsStr::new("a very long number").parse_anyhow::<u32>().err().unwrap());
+//! assert_eq!(
+//!     format!("{:#}", e),
+//!     r#"while parsing "a ve❲…❳umber" as u32: invalid digit found in string"#,
+//! );
+//! ```
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ffi::OsStr;
+use std::fmt::{Debug, Display};
+use std::path::Path;
+use std::process::Command;
+use std::sync::RwLock;
+use unicode_segmentation::UnicodeSegmentation;
+
+const MARKER: &str = "\u{2772}\u{2026}\u{2773}";
+const MARKER_LEN: usize = 3;
+
+static GLOBAL: RwLock<DisplayPolicy> = RwLock::new(DisplayPolicy::DEFAULT);
+
+thread_local! {
+    static OVERRIDE: Cell<Option<DisplayPolicy>> = const { Cell::new(None) };
+}
+
+/// How strings are truncated for display in error contexts
+///
+/// Lengths count `char`s, or extended grapheme clusters if `graphemes` is set, so a cut never
+/// splits a user-perceived character such as an emoji sequence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DisplayPolicy {
+    /// The longest string displayed in full; longer strings are truncated to this length, including the marker
+    pub max_len: usize,
+    /// How much of the start of a truncated string is kept; the remainder after the marker is taken from the end
+    pub head_len: usize,
+    /// Count and cut on grapheme cluster boundaries rather than `char`s
+    pub graphemes: bool,
+}
+
+impl DisplayPolicy {
+    const DEFAULT: Self = DisplayPolicy {
+        max_len: 80,
+        head_len: 40,
+        graphemes: false,
+    };
+
+    /// The process-wide policy
+    pub fn global() -> Self {
+        *GLOBAL.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the process-wide policy
+    pub fn set_global(self) {
+        *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = self;
+    }
+
+    /// The policy in effect on this thread, either overridden by [DisplayPolicy::with] or the global policy
+    pub fn current() -> Self {
+        OVERRIDE.with(Cell::get).unwrap_or_else(Self::global)
+    }
+
+    /// Call `f` with this policy in effect on the current thread, restoring the previous policy afterwards
+    pub fn with<F, R>(self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        struct Restore(Option<DisplayPolicy>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                OVERRIDE.with(|o| o.set(self.0));
+            }
+        }
+
+        let _restore = Restore(OVERRIDE.with(|o| o.replace(Some(self))));
+        f()
+    }
+
+    /// Truncate `s` according to this policy, if it is too long
+    ///
+    /// The result is never longer than `max_len`. If `max_len` leaves no room for the marker,
+    /// only the first `max_len` characters are kept.
+    pub fn truncate<'a>(&self, s: Cow<'a, str>) -> Cow<'a, str> {
+        let bounds = self.bounds(&s);
+        let count = bounds.len();
+
+        if count <= self.max_len {
+            s
+        } else if self.max_len < MARKER_LEN {
+            Cow::from(s[..bounds[self.max_len]].to_string())
+        } else {
+            let room = self.max_len - MARKER_LEN;
+            let head = self.head_len.min(room);
+            let tail = room - head;
+            let tail_start = bounds.get(count - tail).copied().unwrap_or(s.len());
+
+            Cow::from(format!(
+                "{}{}{}",
+                &s[..bounds[head]],
+                MARKER,
+                &s[tail_start..]
+            ))
+        }
+    }
+}
+
+impl DisplayPolicy {
+    /// The start offsets of the characters of `s`, as counted by this policy
+    fn bounds(&self, s: &str) -> Vec<usize> {
+        if self.graphemes {
+            s.grapheme_indices(true).map(|(i, _)| i).collect()
+        } else {
+            s.char_indices().map(|(i, _)| i).collect()
+        }
+    }
+
+    fn len(&self, s: &str) -> usize {
+        if self.graphemes {
+            s.graphemes(true).count()
+        } else {
+            s.chars().count()
+        }
+    }
+}
+
+impl Default for DisplayPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Render `value` with [Debug], truncated according to [DisplayPolicy::current]
+pub fn truncate_debug<T>(value: &T) -> String
+where
+    T: Debug + ?Sized,
+{
+    truncate(format!("{:?}", value).into()).into_owned()
+}
+
+/// Render `value` with [Display], truncated according to [DisplayPolicy::current]
+pub fn truncate_display<T>(value: &T) -> String
+where
+    T: Display + ?Sized,
+{
+    truncate(value.to_string().into()).into_owned()
+}
+
+/// Truncate `s` according to [DisplayPolicy::current]
+pub(crate) fn truncate(s: Cow<'_, str>) -> Cow<'_, str> {
+    DisplayPolicy::current().truncate(s)
+}
+
+/// Quote `path` for an error context, truncated according to [DisplayPolicy::current]
+///
+/// Paths which are not truncated keep the exact `Debug` rendering of [Path::display].
+pub fn path_debug(path: &Path) -> String {
+    let lossy = path.to_string_lossy();
+    match truncate(Cow::Borrowed(&lossy)) {
+        Cow::Borrowed(_) => format!("{:?}", path.display()),
+        Cow::Owned(s) => format!("{:?}", s),
+    }
+}
+
+/// Describe `cmd` for an error context, truncated according to [DisplayPolicy::current]
+///
+/// Commands which are not truncated keep their exact `Debug` rendering. Otherwise the current
+/// directory, environment, program, and arguments are each truncated before quoting, so a cut
+/// never falls within quotes or escapes, and arguments are elided from the middle as a whole,
+/// unless a single argument is elided, which is truncated to fit instead.
+pub(crate) fn command_debug(cmd: &Command) -> String {
+    let policy = DisplayPolicy::current();
+    let full = format!("{:?}", cmd);
+    if policy.len(&full) <= policy.max_len {
+        return full;
+    }
+
+    let quote =
+        |policy: &DisplayPolicy, w: &OsStr| format!("{:?}", policy.truncate(w.to_string_lossy()));
+    let args: Vec<&OsStr> = cmd.get_args().collect();
+    let mut words = command_prefix(cmd, &full, &policy);
+    words.push(quote(&policy, cmd.get_program()));
+    let fixed = words.len();
+    words.extend(args.iter().map(|a| quote(&policy, a)));
+    let lens: Vec<usize> = words.iter().map(|w| policy.len(w)).collect();
+
+    // Keep the prefix, the program, and the arguments fitting within the head, then as many of
+    // the last arguments as fit in the remainder after the marker, counting separating spaces:
+    let mut used = lens[..fixed].iter().sum::<usize>() + fixed - 1;
+    let mut head = fixed;
+    while head < words.len() && used + 1 + lens[head] <= policy.head_len {
+        used += 1 + lens[head];
+        head += 1;
+    }
+    if head == words.len() {
+        return words.join(" ");
+    }
+    let mut tail = words.len();
+    while tail > head && used + 2 + MARKER_LEN + lens[tail - 1] <= policy.max_len {
+        used += 1 + lens[tail - 1];
+        tail -= 1;
+    }
+
+    let mut desc = words[..head].join(" ");
+    if tail == head + 1 {
+        // The quotes and separating space leave this much room for the elided argument:
+        let room = policy.max_len.saturating_sub(used + 3);
+        let narrow = DisplayPolicy {
+            max_len: room.max(MARKER_LEN),
+            head_len: policy.head_len * room / policy.max_len.max(1),
+            ..policy
+        };
+        desc.push(' ');
+        desc.push_str(&quote(&narrow, args[head - fixed]));
+    } else if tail > head {
+        desc.push(' ');
+        desc.push_str(MARKER);
+    }
+    for word in &words[tail..] {
+        desc.push(' ');
+        desc.push_str(word);
+    }
+    desc
+}
+
+/// The words `Debug` renders for `cmd` before its program, for its current directory and
+/// environment, with each value truncated
+fn command_prefix(cmd: &Command, full: &str, policy: &DisplayPolicy) -> Vec<String> {
+    let mut words = vec![];
+    if let Some(dir) = cmd.get_current_dir().filter(|_| full.starts_with("cd ")) {
+        words.push(format!(
+            "cd {:?} &&",
+            policy.truncate(dir.to_string_lossy())
+        ));
+    }
+    // Clearing the environment is not exposed other than by `Debug`, which then omits removals:
+    if full.starts_with("env -i ") || full.contains(" && env -i ") {
+        words.push("env -i".to_string());
+    } else if cmd.get_envs().any(|(_, v)| v.is_none()) {
+        words.push("env".to_string());
+        for (key, _) in cmd.get_envs().filter(|(_, v)| v.is_none()) {
+            words.push(format!("-u {}", policy.truncate(key.to_string_lossy())));
+        }
+    }
+    for (key, value) in cmd.get_envs() {
+        if let Some(value) = value {
+            words.push(format!(
+                "{}={:?}",
+                policy.truncate(key.to_string_lossy()),
+                policy.truncate(value.to_string_lossy())
+            ));
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,112 @@
+use super::DisplayPolicy;
+use std::borrow::Cow;
+use std::path::Path;
+use test_case::test_case;
+
+const SHORT: DisplayPolicy = DisplayPolicy {
+    max_len: 10,
+    head_len: 4,
+    graphemes: false,
+};
+
+#[test_case(SHORT, "0123456789" => "0123456789" ; "at limit")]
+#[test_case(SHORT, "0123456789a" => "0123\u{2772}\u{2026}\u{2773}89a" ; "over limit")]
+#[test_case(DisplayPolicy { head_len: 20, ..SHORT }, "0123456789ab" => "0123456\u{2772}\u{2026}\u{2773}" ; "head exceeds max")]
+#[test_case(DisplayPolicy { max_len: 3, head_len: 1, ..SHORT }, "0123" => "\u{2772}\u{2026}\u{2773}" ; "only marker fits")]
+#[test_case(DisplayPolicy { max_len: 2, head_len: 1, ..SHORT }, "0123" => "01" ; "marker does not fit")]
+#[test_case(SHORT, "e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}" => "e\u{301}e\u{301}\u{2772}\u{2026}\u{2773}\u{301}e\u{301}" ; "chars split graphemes")]
+#[test_case(DisplayPolicy { graphemes: true, ..SHORT }, "e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}" => "e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}" ; "graphemes under limit")]
+#[test_case(DisplayPolicy { graphemes: true, ..SHORT }, "👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽" => "👍🏽👍🏽👍🏽👍🏽\u{2772}\u{2026}\u{2773}👍🏽👍🏽👍🏽" ; "graphemes over limit")]
+fn truncate(policy: DisplayPolicy, input: &str) -> String {
+    policy.truncate(Cow::Borrowed(input)).into_owned()
+}
+
+#[test]
+fn default_matches_global_limit() {
+    let input = "x".repeat(100);
+    let output = DisplayPolicy::default().truncate(Cow::Borrowed(&input));
+    assert_eq!(output.chars().count(), 80);
+    assert!(output.starts_with(&"x".repeat(40)));
+}
+
+#[test]
+fn with_overrides_current_and_restores() {
+    let outer = DisplayPolicy::current();
+    let inner = SHORT.with(|| {
+        let nested = DisplayPolicy::default().with(DisplayPolicy::current);
+        (DisplayPolicy::current(), nested)
+    });
+    assert_eq!(inner, (SHORT, DisplayPolicy::default()));
+    assert_eq!(DisplayPolicy::current(), outer);
+}
+
+#[test_case("/short" => "\"/short\"" ; "short")]
+#[test_case("/a/very/long/path" => "\"/a/v\u{2772}\u{2026}\u{2773}ath\"" ; "long")]
+fn path_debug(input: &str) -> String {
+    SHORT.with(|| super::path_debug(Path::new(input)))
+}
+
+#[test]
+fn command_context() {
+    use crate::CommandAnyhow;
+
+    let cmd = std::process::Command::new("a-very-long-program");
+    assert_eq!(
+        SHORT.with(|| cmd.anyhow_context()),
+        "command: \"a-ve\u{2772}\u{2026}\u{2773}ram\""
+    );
+}
+
+#[test]
+fn command_context_elides_arguments() {
+    use crate::CommandAnyhow;
+
+    let mut cmd = std::process::Command::new("ls");
+    cmd.args(["-l", "a", "b", "c", "d\"e"]);
+    assert_eq!(
+        DisplayPolicy {
+            max_len: 16,
+            head_len: 8,
+            ..SHORT
+        }
+        .with(|| cmd.anyhow_context()),
+        "command: \"ls\" \u{2772}\u{2026}\u{2773} \"d\\\"e\""
+    );
+}
+
+#[test]
+fn command_context_keeps_directory_and_environment() {
+    use crate::CommandAnyhow;
+
+    let mut cmd = std::process::Command::new("ls");
+    cmd.current_dir("/a/very/long/directory")
+        .env("FOO", "bar")
+        .env_remove("BAZ")
+        .args(["-l", "a", "b", "c", "d"]);
+    assert_eq!(
+        DisplayPolicy {
+            max_len: 16,
+            head_len: 8,
+            ..SHORT
+        }
+        .with(|| cmd.anyhow_context()),
+        "command: cd \"/a/very/\u{2772}\u{2026}\u{2773}ctory\" && env -u BAZ FOO=\"bar\" \"ls\" \u{2772}\u{2026}\u{2773}"
+    );
+}
+
+#[test]
+fn command_context_truncates_single_elided_argument() {
+    use crate::CommandAnyhow;
+
+    let mut cmd = std::process::Command::new("ls");
+    cmd.arg("a-very-long-argument-which-does-not-fit");
+    assert_eq!(
+        DisplayPolicy {
+            max_len: 24,
+            head_len: 12,
+            ..SHORT
+        }
+        .with(|| cmd.anyhow_context()),
+        "command: \"ls\" \"a-very-l\u{2772}\u{2026}\u{2773}ot-fit\""
+    );
+}
@@ -1,6 +1,76 @@
 //! Wrappers for [mod@std::env]
+use crate::strext::parse_context;
+use crate::ResultAnyhow;
+use crate::{OsStrAnyhow, OsStringAnyhow, PathAnyhow};
 use anyhow::{anyhow, Context};
+use std::collections::BTreeMap;
 use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+mod dotenv;
+mod expand;
+mod paths;
+mod reader;
+mod scoped;
+
+pub use self::dotenv::{load_dotenv_anyhow, parse_dotenv};
+pub use self::expand::expand;
+pub(crate) use self::expand::expand_path;
+pub(crate) use self::paths::which_in;
+pub use self::paths::{join_paths_anyhow, split_paths_var, which};
+pub use self::reader::Reader;
+pub use self::scoped::{ScopedCurrentDir, ScopedVar};
+
+#[cfg(feature = "derive")]
+pub use anyhow_std_macros::FromEnv;
+
+/// Types which can be loaded from environment variables
+///
+/// With the `derive` feature, `#[derive(FromEnv)]` implements this for structs with named
+/// fields. Each field is parsed with [FromStr] from the variable named by the uppercased field
+/// name, except `bool` fields, which accept the spellings of [var_bool]. Every missing or
+/// invalid variable is reported together, as with [Reader]. The derive accepts these attributes:
+///
+/// - `#[from_env(prefix = "APP_")]` on the struct prefixes every key.
+/// - `#[from_env(key = "PORT")]` on a field overrides its key.
+/// - `#[from_env(default = "8080")]` on a field is parsed in place of an absent variable.
+/// - Fields of type `Option<T>` are `None` when their variable is absent.
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # fn main() -> anyhow::Result<()> {
+/// use anyhow_std::env::FromEnv;
+///
+/// #[derive(FromEnv)]
+/// #[from_env(prefix = "DOCTEST_")]
+/// struct Config {
+///     host: String,
+///     #[from_env(default = "8080")]
+///     port: u16,
+///     #[from_env(key = "LOG")]
+///     log_level: Option<String>,
+/// }
+///
+/// std::env::set_var("DOCTEST_HOST", "localhost");
+/// let config = Config::from_env()?;
+/// assert_eq!(config.host, "localhost");
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.log_level, None);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+pub trait FromEnv: Sized {
+    /// Load from environment variables whose keys all begin with `prefix`
+    fn from_env_with_prefix(prefix: &str) -> anyhow::Result<Self>;
+
+    /// Load from environment variables
+    fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_with_prefix("")
+    }
+}
 
 /// Wrap [std::env::var] providing `key` in error context
 pub fn var<K>(key: K) -> anyhow::Result<String>
@@ -8,7 +78,9 @@ where
     K: AsRef<OsStr>,
 {
     let os = key.as_ref();
-    std::env::var(os).with_context(|| format!("environment variable {:?}", os.to_string_lossy()))
+    var_without_context(os)
+        .with_context(|| key_context(os))
+        .with_scope_context()
 }
 
 /// Wrap [std::env::var_os], converting `None` to an error with `key` in error context
@@ -18,21 +90,287 @@ where
 {
     let os = key.as_ref();
     var_os_without_context(os)
-        .with_context(|| format!("environment variable {:?}", os.to_string_lossy()))
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Wrap [std::env::set_var], providing `key` in error context
+///
+/// Unlike [std::env::set_var], invalid keys or values produce errors rather than panics.
+pub fn set_var<K, V>(key: K, value: V) -> anyhow::Result<()>
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    let os = key.as_ref();
+    check_key(os)
+        .and_then(|()| check_value(value.as_ref()))
+        .map(|()| std::env::set_var(os, value))
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Wrap [std::env::remove_var], providing `key` in error context
+///
+/// Unlike [std::env::remove_var], invalid keys produce errors rather than panics.
+pub fn remove_var<K>(key: K) -> anyhow::Result<()>
+where
+    K: AsRef<OsStr>,
+{
+    let os = key.as_ref();
+    check_key(os)
+        .map(|()| std::env::remove_var(os))
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Like [var], except an absent variable is `Ok(None)`; non-utf8 values remain errors
+pub fn var_opt<K>(key: K) -> anyhow::Result<Option<String>>
+where
+    K: AsRef<OsStr>,
+{
+    let os = key.as_ref();
+    var_opt_without_context(os)
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Parse the value of [var] with [FromStr], providing `key` and the value in error context
+pub fn var_parse<T, K>(key: K) -> anyhow::Result<T>
+where
+    K: AsRef<OsStr>,
+    T: FromStr,
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let os = key.as_ref();
+    var_parse_without_context(os)
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Parse the value of [var] with [FromStr], or return `default` when the variable is absent
+pub fn var_or<T, K>(key: K, default: T) -> anyhow::Result<T>
+where
+    K: AsRef<OsStr>,
+    T: FromStr,
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let os = key.as_ref();
+    var_or_without_context(os, default)
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Parse the value of [var] as a `bool`, providing `key` and the value in error context
+///
+/// The accepted spellings are `1`, `true`, `yes`, and `on` for `true`, and `0`, `false`,
+/// `no`, and `off` for `false`, all ASCII case-insensitive. Any other value is an error.
+pub fn var_bool<K>(key: K) -> anyhow::Result<bool>
+where
+    K: AsRef<OsStr>,
+{
+    let os = key.as_ref();
+    var_bool_without_context(os)
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Wrap [std::env::vars_os], converting entries to utf8 with the (lossy) key as error context
+///
+/// Unlike [std::env::vars], which panics, each non-utf8 entry produces an error.
+pub fn vars_anyhow() -> impl Iterator<Item = anyhow::Result<(String, String)>> {
+    std::env::vars_os().map(var_entry)
+}
+
+/// Collect the variables whose keys begin with `prefix`, with `prefix` removed from the keys
+///
+/// This fails on the first non-utf8 variable with a matching key, as with [vars_anyhow].
+pub fn vars_with_prefix(prefix: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    vars_with_prefix_from(std::env::vars_os(), prefix)
+}
+
+fn vars_with_prefix_from<I>(vars: I, prefix: &str) -> anyhow::Result<BTreeMap<String, String>>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    vars.into_iter()
+        .filter(|(key, _)| key.to_string_lossy().starts_with(prefix))
+        .map(|entry| var_entry(entry).map(|(key, value)| (key[prefix.len()..].to_string(), value)))
+        .collect()
+}
+
+fn var_entry((key, value): (OsString, OsString)) -> anyhow::Result<(String, String)> {
+    let lossy_key = key.to_string_lossy().into_owned();
+    key.into_string_anyhow()
+        .and_then(|key| value.into_string_anyhow().map(|value| (key, value)))
+        .with_context(|| key_context(lossy_key.as_ref()))
+        .with_scope_context()
+}
+
+/// Wrap [std::env::current_dir] with error context
+pub fn current_dir() -> anyhow::Result<PathBuf> {
+    std::env::current_dir()
+        .context("while reading the current directory")
+        .with_scope_context()
+}
+
+/// Wrap [std::env::current_exe] with error context
+///
+/// On Linux, failures explain that the executable is located via `/proc/self/exe`.
+pub fn current_exe() -> anyhow::Result<PathBuf> {
+    let res = std::env::current_exe();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let res = res.context("the current executable is located via \"/proc/self/exe\", which requires /proc to be mounted");
+    res.context("while locating the current executable")
+        .with_scope_context()
+}
+
+/// Wrap [std::env::home_dir], converting `None` to an error
+pub fn home_dir() -> anyhow::Result<PathBuf> {
+    // `home_dir` is deprecated on older toolchains, prior to fixing its Windows behavior:
+    #[allow(deprecated)]
+    std::env::home_dir()
+        .ok_or_else(|| anyhow!("the home directory could not be determined"))
+        .with_scope_context()
+}
+
+/// Wrap [std::env::temp_dir], returning an error if the directory does not exist
+pub fn temp_dir() -> anyhow::Result<PathBuf> {
+    temp_dir_from(std::env::temp_dir())
+}
+
+fn temp_dir_from(dir: PathBuf) -> anyhow::Result<PathBuf> {
+    dir.metadata_anyhow()
+        .and_then(|md| {
+            if md.is_dir() {
+                Ok(())
+            } else {
+                Err(anyhow!("not a directory")).with_path_context(&dir)
+            }
+        })
+        .context("while locating the temporary directory")
+        .with_scope_context()?;
+    Ok(dir)
+}
+
+/// Collect [std::env::args_os] as utf8 `String`s
+///
+/// Unlike [std::env::args], which panics, a non-utf8 argument produces an error giving its
+/// index and a lossy rendering of it.
+pub fn args_utf8() -> anyhow::Result<Vec<String>> {
+    args_utf8_from(std::env::args_os())
+}
+
+fn args_utf8_from<I>(args: I) -> anyhow::Result<Vec<String>>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    args.into_iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            arg.into_string_anyhow()
+                .with_context(|| format!("command line argument {}", i))
+        })
+        .collect::<anyhow::Result<_>>()
+        .with_scope_context()
+}
+
+fn key_context(key: &OsStr) -> String {
+    format!("environment variable {:?}", key.to_string_lossy())
+}
+
+fn var_without_context(key: &OsStr) -> anyhow::Result<String> {
+    check_key(key)?;
+    std::env::var(key).map_err(anyhow::Error::from)
+}
+
+fn var_opt_without_context(key: &OsStr) -> anyhow::Result<Option<String>> {
+    check_key(key)?;
+    std::env::var_os(key)
+        .map(|value| value.to_str_anyhow().map(String::from))
+        .transpose()
+}
+
+fn var_parse_without_context<T>(key: &OsStr) -> anyhow::Result<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    var_os_without_context(key).and_then(|value| value.parse_anyhow())
+}
+
+fn var_or_without_context<T>(key: &OsStr, default: T) -> anyhow::Result<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    check_key(key)?;
+    match std::env::var_os(key) {
+        None => Ok(default),
+        Some(value) => value.parse_anyhow(),
+    }
+}
+
+fn var_bool_without_context(key: &OsStr) -> anyhow::Result<bool> {
+    var_os_without_context(key).and_then(|value| parse_bool(&value))
+}
+
+const BOOL_SPELLINGS: [(&str, bool); 8] = [
+    ("1", true),
+    ("true", true),
+    ("yes", true),
+    ("on", true),
+    ("0", false),
+    ("false", false),
+    ("no", false),
+    ("off", false),
+];
+
+fn parse_bool(value: &OsStr) -> anyhow::Result<bool> {
+    let s = value.to_str_anyhow()?;
+    BOOL_SPELLINGS
+        .iter()
+        .find(|(spelling, _)| spelling.eq_ignore_ascii_case(s))
+        .map(|&(_, b)| b)
+        .ok_or_else(|| {
+            anyhow!(
+                "expected one of {}",
+                BOOL_SPELLINGS.map(|(spelling, _)| spelling).join(", ")
+            )
+        })
+        .with_context(|| parse_context::<bool>(s))
 }
 
 fn var_os_without_context<K>(key: K) -> anyhow::Result<OsString>
 where
     K: AsRef<OsStr>,
 {
-    let lossy_cow = key.as_ref().to_string_lossy();
+    check_key(key.as_ref())?;
+    std::env::var_os(key).ok_or_else(|| anyhow!("environment variable not found"))
+}
+
+/// Reject keys which [std::env::set_var] would panic on, and which can never be found
+fn check_key(key: &OsStr) -> anyhow::Result<()> {
+    if key.is_empty() {
+        return Err(anyhow!("environment variable name is empty"));
+    }
+    let lossy_cow = key.to_string_lossy();
     let lossy_str = lossy_cow.as_ref();
     for c in ['=', '\0'] {
         if lossy_str.contains(c) {
             return Err(anyhow!("environment variable contains {:?}", c));
         }
     }
-    std::env::var_os(key).ok_or_else(|| anyhow!("environment variable not found"))
+    Ok(())
+}
+
+/// Reject values which [std::env::set_var] would panic on
+fn check_value(value: &OsStr) -> anyhow::Result<()> {
+    if value.to_string_lossy().contains('\0') {
+        Err(anyhow!("environment variable value contains '\\0'"))
+    } else {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,218 @@
+use crate::env::set_var;
+use crate::PathAnyhow;
+use crate::ResultAnyhow;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+/// Parse the `.env` file at `path` into key/value pairs, without modifying the environment
+///
+/// Each line holds a `KEY=value` assignment, optionally preceded by `export`. Values may be
+/// unquoted, with surrounding whitespace and any trailing ` # comment` removed; single quoted,
+/// which is literal; or double quoted, which may span lines and supports the escapes `\n`,
+/// `\r`, `\t`, `\"`, `\\`, and `\$`. Blank lines and lines beginning with `#` are ignored.
+///
+/// Syntax errors give the path, and the line and column of the error.
+pub fn parse_dotenv<P>(path: P) -> anyhow::Result<Vec<(String, String)>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let text = path.read_to_string_anyhow()?;
+    parse_dotenv_str(&text).with_path_context(path)
+}
+
+/// Set the environment variables assigned by the `.env` file at `path`, as parsed by [parse_dotenv]
+///
+/// If `overwrite` is false, variables which are already set keep their values.
+pub fn load_dotenv_anyhow<P>(path: P, overwrite: bool) -> anyhow::Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    for (key, value) in parse_dotenv(path)? {
+        if overwrite || std::env::var_os(&key).is_none() {
+            set_var(&key, &value).with_path_context(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_dotenv_str(text: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut cursor = Cursor {
+        chars: text.chars().peekable(),
+        line: 1,
+        column: 1,
+    };
+    let mut assignments = vec![];
+
+    loop {
+        cursor.skip_blanks();
+        match cursor.peek() {
+            None => return Ok(assignments),
+            Some('\n') => {
+                cursor.next();
+            }
+            Some('#') => cursor.skip_line(),
+            Some(_) => {
+                let mut key = cursor.parse_key()?;
+                if key == "export" && cursor.peek().is_some_and(|c| c == ' ' || c == '\t') {
+                    cursor.skip_blanks();
+                    key = cursor.parse_key()?;
+                }
+                cursor.skip_blanks();
+                cursor.expect('=')?;
+                cursor.skip_blanks();
+                let value = cursor.parse_value()?;
+                assignments.push((key, value));
+            }
+        }
+    }
+}
+
+fn syntax_error((line, column): (usize, usize), msg: &str) -> anyhow::Error {
+    anyhow::Error::msg(msg.to_string()).context(format!("line {}, column {}", line, column))
+}
+
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    fn skip_blanks(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| c == ' ' || c == '\t' || c == '\r')
+        {
+            self.next();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while self.next().is_some_and(|c| c != '\n') {}
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        if self.peek() == Some(expected) {
+            self.next();
+            Ok(())
+        } else {
+            Err(syntax_error(
+                self.position(),
+                &format!("expected {:?}", expected),
+            ))
+        }
+    }
+
+    fn parse_key(&mut self) -> anyhow::Result<String> {
+        let start = self.position();
+        let mut key = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|&c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            key.push(c);
+            self.next();
+        }
+        if key.is_empty() || key.starts_with(|c: char| c.is_ascii_digit()) {
+            Err(syntax_error(start, "expected a variable name"))
+        } else {
+            Ok(key)
+        }
+    }
+
+    fn parse_value(&mut self) -> anyhow::Result<String> {
+        let value = match self.peek() {
+            Some('\'') => self.parse_single_quoted()?,
+            Some('"') => self.parse_double_quoted()?,
+            _ => return Ok(self.parse_unquoted()),
+        };
+
+        self.skip_blanks();
+        match self.peek() {
+            None | Some('\n') => {}
+            Some('#') => self.skip_line(),
+            Some(_) => {
+                return Err(syntax_error(
+                    self.position(),
+                    "unexpected character after quoted value",
+                ))
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_unquoted(&mut self) -> String {
+        let mut value = String::new();
+        while let Some(c) = self.peek().filter(|&c| c != '\n') {
+            if c == '#' && value.ends_with([' ', '\t']) {
+                self.skip_line();
+                break;
+            }
+            value.push(c);
+            self.next();
+        }
+        value.trim_end().to_string()
+    }
+
+    fn parse_single_quoted(&mut self) -> anyhow::Result<String> {
+        let start = self.position();
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(syntax_error(start, "unterminated single quote")),
+            }
+        }
+    }
+
+    fn parse_double_quoted(&mut self) -> anyhow::Result<String> {
+        let start = self.position();
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => return Err(syntax_error(start, "unterminated double quote")),
+                },
+                Some(c) => value.push(c),
+                None => return Err(syntax_error(start, "unterminated double quote")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
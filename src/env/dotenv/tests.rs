@@ -0,0 +1,75 @@
+use crate::testutils::{err_str, stringify_error};
+use test_case::test_case;
+
+#[test_case("" => Ok(vec![]) ; "empty")]
+#[test_case(
+    "# comment\n\nA=1\nexport B = two words  # trailing comment\nC=#not-a-comment\n"
+    => Ok(vec![
+        ("A".to_string(), "1".to_string()),
+        ("B".to_string(), "two words".to_string()),
+        ("C".to_string(), "#not-a-comment".to_string()),
+    ])
+    ; "unquoted"
+)]
+#[test_case(
+    "A='literal \\n $x' # comment\nB=\"line\\none\\t\\\"q\\\" \\$x\"\nC=\"multi\nline\"\nD=''\n"
+    => Ok(vec![
+        ("A".to_string(), "literal \\n $x".to_string()),
+        ("B".to_string(), "line\none\t\"q\" $x".to_string()),
+        ("C".to_string(), "multi\nline".to_string()),
+        ("D".to_string(), "".to_string()),
+    ])
+    ; "quoted"
+)]
+#[test_case("A=1\r\nB=2\r\n" => Ok(vec![
+        ("A".to_string(), "1".to_string()),
+        ("B".to_string(), "2".to_string()),
+    ])
+    ; "crlf"
+)]
+#[test_case("A=1\n  B\n" => err_str("line 2, column 4: expected '='") ; "missing equals")]
+#[test_case("A=1\n=2\n" => err_str("line 2, column 1: expected a variable name") ; "missing key")]
+#[test_case("9A=1\n" => err_str("line 1, column 1: expected a variable name") ; "digit key")]
+#[test_case(
+    "A=1\nB=\"abc\nC=3\n"
+    => err_str("line 2, column 3: unterminated double quote")
+    ; "unterminated double"
+)]
+#[test_case("A='abc" => err_str("line 1, column 3: unterminated single quote") ; "unterminated single")]
+#[test_case(
+    "A='abc' def"
+    => err_str("line 1, column 9: unexpected character after quoted value")
+    ; "trailing garbage"
+)]
+fn parse_dotenv_str(text: &str) -> Result<Vec<(String, String)>, String> {
+    stringify_error(super::parse_dotenv_str(text))
+}
+
+#[test]
+fn load_dotenv() -> anyhow::Result<()> {
+    use crate::{env, PathAnyhow};
+
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join(".env");
+    path.write_anyhow("ANYHOW_STD_TEST_DOTENV_NEW=new\nANYHOW_STD_TEST_DOTENV_SET=from-file\n")?;
+
+    let _new = env::ScopedVar::remove("ANYHOW_STD_TEST_DOTENV_NEW")?;
+    let _set = env::ScopedVar::set("ANYHOW_STD_TEST_DOTENV_SET", "preset")?;
+
+    env::load_dotenv_anyhow(&path, false)?;
+    assert_eq!(env::var("ANYHOW_STD_TEST_DOTENV_NEW")?, "new");
+    assert_eq!(env::var("ANYHOW_STD_TEST_DOTENV_SET")?, "preset");
+
+    env::load_dotenv_anyhow(&path, true)?;
+    assert_eq!(env::var("ANYHOW_STD_TEST_DOTENV_SET")?, "from-file");
+
+    path.write_anyhow("OK=1\nBAD VALUE\n")?;
+    assert_eq!(
+        stringify_error(env::parse_dotenv(&path)),
+        Err(format!(
+            "while processing path {:?}: line 2, column 5: expected '='",
+            path.display()
+        )),
+    );
+    Ok(())
+}
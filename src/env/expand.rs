@@ -0,0 +1,145 @@
+use crate::display::truncate;
+use crate::env;
+use crate::ResultAnyhow;
+use anyhow::{anyhow, Context};
+use std::path::PathBuf;
+
+/// Expand environment variable references in `template`, looking them up with [env::var]
+///
+/// References may be written `$NAME`, `${NAME}`, or `${NAME:-default}`, where `default` is
+/// itself expanded and used when `NAME` is unset or empty. `$$` produces a literal `$`, as does
+/// a `$` which does not begin a reference.
+///
+/// Errors provide the template along with the name and 1-based column of the failing reference.
+pub fn expand(template: &str) -> anyhow::Result<String> {
+    expand_after(template, 0)
+}
+
+/// Expand `template` after its first `skip` chars, which are omitted from the result but still counted in columns
+fn expand_after(template: &str, skip: usize) -> anyhow::Result<String> {
+    let chars: Vec<char> = template.chars().skip(skip).collect();
+    expand_chars(&chars, 1 + skip)
+        .with_context(|| format!("while expanding {:?}", truncate(template.into())))
+        .with_scope_context()
+}
+
+/// Expand a leading `~` to [env::home_dir], and expand the rest with [expand]
+pub(crate) fn expand_path(s: &str) -> anyhow::Result<PathBuf> {
+    match s.strip_prefix('~') {
+        Some("") => env::home_dir(),
+        Some(rest) if rest.starts_with(std::path::is_separator) => {
+            let rest = expand_after(s, 1)?;
+            Ok(env::home_dir()?.join(rest.trim_start_matches(std::path::is_separator)))
+        }
+        _ => expand(s).map(PathBuf::from),
+    }
+}
+
+fn expand_chars(chars: &[char], first_column: usize) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let column = first_column + i;
+        if chars[i] != '$' {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        match chars.get(i + 1) {
+            Some('$') => {
+                out.push('$');
+                i += 2;
+            }
+            Some('{') => {
+                let close = find_close_brace(chars, i + 2)
+                    .ok_or_else(|| anyhow!("unterminated \"${{\" at column {}", column))?;
+                let inner = &chars[i + 2..close];
+                let namelen = name_len(inner);
+                if namelen == 0 {
+                    return Err(anyhow!("invalid variable name at column {}", column + 2));
+                }
+                let name: String = inner[..namelen].iter().collect();
+                match &inner[namelen..] {
+                    [] => out.push_str(&lookup(&name, column)?),
+                    [':', '-', default @ ..] => {
+                        let value = env::var_opt_without_context(name.as_ref())
+                            .with_context(|| reference_context(&name, column))?;
+                        match value.filter(|v| !v.is_empty()) {
+                            Some(v) => out.push_str(&v),
+                            None => out.push_str(&expand_chars(default, column + 2 + namelen + 2)?),
+                        }
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "unsupported expansion at column {}",
+                            column + 2 + namelen
+                        ))
+                    }
+                }
+                i = close + 1;
+            }
+            Some(_) => {
+                let namelen = name_len(&chars[i + 1..]);
+                if namelen == 0 {
+                    out.push('$');
+                    i += 1;
+                } else {
+                    let name: String = chars[i + 1..i + 1 + namelen].iter().collect();
+                    out.push_str(&lookup(&name, column)?);
+                    i += 1 + namelen;
+                }
+            }
+            None => {
+                out.push('$');
+                i += 1;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn lookup(name: &str, column: usize) -> anyhow::Result<String> {
+    env::var_without_context(name.as_ref()).with_context(|| reference_context(name, column))
+}
+
+fn reference_context(name: &str, column: usize) -> String {
+    format!("variable {:?} at column {}", name, column)
+}
+
+/// The length of the variable name at the start of `chars`, or 0 if there is none
+fn name_len(chars: &[char]) -> usize {
+    match chars.first() {
+        Some(c) if c.is_ascii_alphabetic() || *c == '_' => chars
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+            .count(),
+        _ => 0,
+    }
+}
+
+/// Find the `}` closing a `${` whose contents begin at `start`, accounting for nesting
+fn find_close_brace(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut i = start;
+    while i < chars.len() {
+        match (chars[i], chars.get(i + 1)) {
+            ('$', Some('{')) => {
+                depth += 1;
+                i += 2;
+                continue;
+            }
+            ('}', _) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
@@ -0,0 +1,115 @@
+use crate::env::{key_context, var_os_without_context};
+use crate::ResultAnyhow;
+use anyhow::Context;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Split a `PATH`-style variable into paths with [std::env::split_paths], providing `key` in error context
+pub fn split_paths_var<K>(key: K) -> anyhow::Result<Vec<PathBuf>>
+where
+    K: AsRef<OsStr>,
+{
+    let os = key.as_ref();
+    var_os_without_context(os)
+        .map(|value| std::env::split_paths(&value).collect())
+        .with_context(|| key_context(os))
+        .with_scope_context()
+}
+
+/// Wrap [std::env::join_paths], providing the offending path element as error context
+pub fn join_paths_anyhow<I, T>(paths: I) -> anyhow::Result<OsString>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<OsStr>,
+{
+    let paths: Vec<T> = paths.into_iter().collect();
+    std::env::join_paths(paths.iter())
+        .map_err(|e| {
+            let culprit = paths
+                .iter()
+                .map(AsRef::as_ref)
+                .enumerate()
+                .find(|(_, p)| std::env::join_paths([p]).is_err());
+
+            match culprit {
+                Some((i, p)) => anyhow::Error::from(e).context(format!(
+                    "path element {} {:?}",
+                    i,
+                    p.to_string_lossy()
+                )),
+                None => anyhow::Error::from(e),
+            }
+        })
+        .with_scope_context()
+}
+
+/// Search the `PATH` variable for an executable `program`, as a shell would
+///
+/// A `program` with a directory component is checked directly rather than searched for. On
+/// failure, the error lists every directory searched.
+pub fn which<P>(program: P) -> anyhow::Result<PathBuf>
+where
+    P: AsRef<OsStr>,
+{
+    let program = program.as_ref();
+    if Path::new(program).components().count() != 1 {
+        let path = Path::new(program);
+        return if is_executable(path) {
+            Ok(path.to_path_buf())
+        } else {
+            Err(anyhow::Error::msg("not an executable file")).with_path_context(path)
+        };
+    }
+
+    let pathvar = var_os_without_context("PATH")
+        .with_context(|| key_context("PATH".as_ref()))
+        .with_scope_context()?;
+    which_in(program, &pathvar).with_scope_context()
+}
+
+/// Search the `PATH`-style `pathvar` for an executable `program`
+pub(crate) fn which_in(program: &OsStr, pathvar: &OsStr) -> anyhow::Result<PathBuf> {
+    let dirs: Vec<PathBuf> = std::env::split_paths(pathvar).collect();
+    dirs.iter()
+        .flat_map(|dir| candidates(dir, program))
+        .find(|candidate| is_executable(candidate))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "program {:?} not found in PATH directories {:?}",
+                program.to_string_lossy(),
+                dirs
+            )
+        })
+}
+
+#[cfg(not(target_family = "windows"))]
+fn candidates(dir: &Path, program: &OsStr) -> Vec<PathBuf> {
+    vec![dir.join(program)]
+}
+
+#[cfg(target_family = "windows")]
+fn candidates(dir: &Path, program: &OsStr) -> Vec<PathBuf> {
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let base = dir.join(program);
+    std::iter::once(base.clone())
+        .chain(pathext.split(';').filter(|ext| !ext.is_empty()).map(|ext| {
+            let mut candidate = base.clone().into_os_string();
+            candidate.push(ext);
+            PathBuf::from(candidate)
+        }))
+        .collect()
+}
+
+#[cfg(target_family = "unix")]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|md| md.is_file() && md.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_family = "unix"))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
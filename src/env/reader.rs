@@ -0,0 +1,180 @@
+use crate::env;
+use crate::ResultAnyhow;
+use crate::StrAnyhow;
+use std::ffi::{OsStr, OsString};
+use std::str::FromStr;
+
+/// Read many environment variables, collecting every failure into a single error
+///
+/// Each lookup method returns `None` on failure and records the key with its reason, so a
+/// caller can check all of its configuration before reporting, rather than failing on the first
+/// missing or invalid variable:
+///
+/// ```
+/// let mut reader = anyhow_std::env::Reader::new();
+/// let host = reader.var("!% HOST SHOULD NOT EXIST %!");
+/// let port = reader.var_parse::<u16, _>("!% PORT SHOULD NOT EXIST %!");
+/// let res = reader.finish();
+///
+/// assert_eq!((host, port), (None, None));
+/// assert_eq!(
+///     format!("{:#}", res.err().unwrap()),
+///     r#"2 environment variable errors:
+/// - "!% HOST SHOULD NOT EXIST %!": environment variable not found
+/// - "!% PORT SHOULD NOT EXIST %!": environment variable not found"#,
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct Reader {
+    failures: Vec<(String, String)>,
+}
+
+impl Reader {
+    /// Construct a [Reader] with no failures
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up [env::var], recording failures
+    pub fn var<K>(&mut self, key: K) -> Option<String>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        self.check(key, env::var_without_context(key))
+    }
+
+    /// Look up [env::var_os], recording failures
+    pub fn var_os<K>(&mut self, key: K) -> Option<OsString>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        self.check(key, env::var_os_without_context(key))
+    }
+
+    /// Look up [env::var_opt], recording failures
+    pub fn var_opt<K>(&mut self, key: K) -> Option<Option<String>>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        self.check(key, env::var_opt_without_context(key))
+    }
+
+    /// Look up [env::var_parse], recording failures
+    pub fn var_parse<T, K>(&mut self, key: K) -> Option<T>
+    where
+        K: AsRef<OsStr>,
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let key = key.as_ref();
+        self.check(key, env::var_parse_without_context(key))
+    }
+
+    /// Look up [env::var_or], recording failures
+    pub fn var_or<T, K>(&mut self, key: K, default: T) -> Option<T>
+    where
+        K: AsRef<OsStr>,
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let key = key.as_ref();
+        self.check(key, env::var_or_without_context(key, default))
+    }
+
+    /// Look up [env::var_parse], returning `Some(None)` if the variable is absent, recording failures
+    pub fn var_parse_opt<T, K>(&mut self, key: K) -> Option<Option<T>>
+    where
+        K: AsRef<OsStr>,
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let key = key.as_ref();
+        let res = env::var_opt_without_context(key)
+            .and_then(|optval| optval.map(|v| v.parse_anyhow()).transpose());
+        self.check(key, res)
+    }
+
+    /// Look up [env::var_parse], parsing `default` as if it were the value when the variable is absent, recording failures
+    pub fn var_parse_or<T, K>(&mut self, key: K, default: &str) -> Option<T>
+    where
+        K: AsRef<OsStr>,
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let key = key.as_ref();
+        let res = env::var_opt_without_context(key)
+            .and_then(|optval| optval.as_deref().unwrap_or(default).parse_anyhow());
+        self.check(key, res)
+    }
+
+    /// Look up [env::var_bool], recording failures
+    pub fn var_bool<K>(&mut self, key: K) -> Option<bool>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        self.check(key, env::var_bool_without_context(key))
+    }
+
+    /// Look up [env::var_bool], returning `Some(None)` if the variable is absent, recording failures
+    pub fn var_bool_opt<K>(&mut self, key: K) -> Option<Option<bool>>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        let res = env::check_key(key).and_then(|()| {
+            std::env::var_os(key)
+                .map(|v| env::parse_bool(&v))
+                .transpose()
+        });
+        self.check(key, res)
+    }
+
+    /// Look up [env::var_bool], parsing `default` as if it were the value when the variable is absent, recording failures
+    pub fn var_bool_or<K>(&mut self, key: K, default: &str) -> Option<bool>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        let res = env::check_key(key).and_then(|()| {
+            env::parse_bool(&std::env::var_os(key).unwrap_or_else(|| default.into()))
+        });
+        self.check(key, res)
+    }
+
+    /// Record the failure of any lookup or validation of `key`
+    ///
+    /// The error for `res` should not repeat `key`, since it is listed with the failure.
+    pub fn check<T, K>(&mut self, key: K, res: anyhow::Result<T>) -> Option<T>
+    where
+        K: AsRef<OsStr>,
+    {
+        res.map_err(|e| {
+            self.failures.push((
+                key.as_ref().to_string_lossy().into_owned(),
+                format!("{:#}", e),
+            ))
+        })
+        .ok()
+    }
+
+    /// Return an error listing every recorded failure, if any
+    pub fn finish(self) -> anyhow::Result<()> {
+        match self.failures.len() {
+            0 => Ok(()),
+            n => Err(anyhow::Error::msg(format!(
+                "{} environment variable error{}:{}",
+                n,
+                if n == 1 { "" } else { "s" },
+                self.failures
+                    .iter()
+                    .map(|(key, reason)| format!("\n- {:?}: {}", key, reason))
+                    .collect::<String>(),
+            )))
+            .with_scope_context(),
+        }
+    }
+}
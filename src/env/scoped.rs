@@ -0,0 +1,145 @@
+use crate::env::{self, check_key, check_value, key_context};
+use crate::{PathAnyhow, ResultAnyhow};
+use anyhow::Context;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::thread::ThreadId;
+
+/// Set an environment variable until dropped, then restore its previous value or absence
+///
+/// All [ScopedVar] and [ScopedCurrentDir] guards share a global lock, so while a guard
+/// exists, other threads block on creating their own guards. Guards may be nested within a
+/// single thread, and should be dropped in reverse order of creation. Code which mutates the
+/// environment without a guard is not serialized.
+#[derive(Debug)]
+pub struct ScopedVar {
+    key: OsString,
+    previous: Option<OsString>,
+    _lock: GlobalLock,
+}
+
+impl ScopedVar {
+    /// Set `key` to `value` until the returned guard is dropped
+    pub fn set<K, V>(key: K, value: V) -> anyhow::Result<Self>
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        check_key(key)
+            .and_then(|()| check_value(value))
+            .with_context(|| key_context(key))
+            .with_scope_context()?;
+        let guard = Self::acquire(key);
+        std::env::set_var(key, value);
+        Ok(guard)
+    }
+
+    /// Remove `key` until the returned guard is dropped
+    pub fn remove<K>(key: K) -> anyhow::Result<Self>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        check_key(key)
+            .with_context(|| key_context(key))
+            .with_scope_context()?;
+        let guard = Self::acquire(key);
+        std::env::remove_var(key);
+        Ok(guard)
+    }
+
+    fn acquire(key: &OsStr) -> Self {
+        let lock = GlobalLock::acquire();
+        ScopedVar {
+            key: key.to_os_string(),
+            previous: std::env::var_os(key),
+            _lock: lock,
+        }
+    }
+}
+
+impl Drop for ScopedVar {
+    fn drop(&mut self) {
+        match self.previous.take() {
+            Some(value) => std::env::set_var(&self.key, value),
+            None => std::env::remove_var(&self.key),
+        }
+    }
+}
+
+/// Set the current directory until dropped, then restore the previous current directory
+///
+/// This shares a global lock with [ScopedVar]; see its documentation.
+#[derive(Debug)]
+pub struct ScopedCurrentDir {
+    previous: PathBuf,
+    _lock: GlobalLock,
+}
+
+impl ScopedCurrentDir {
+    /// Change the current directory to `path` until the returned guard is dropped
+    pub fn set<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let lock = GlobalLock::acquire();
+        let previous = env::current_dir()?;
+        path.as_ref().set_to_current_dir_anyhow()?;
+        Ok(ScopedCurrentDir {
+            previous,
+            _lock: lock,
+        })
+    }
+}
+
+impl Drop for ScopedCurrentDir {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop, and the previous directory may have been removed.
+        let _ = self.previous.set_to_current_dir_anyhow();
+    }
+}
+
+/// A reentrant global lock, so a thread may hold multiple guards at once
+#[derive(Debug)]
+pub(super) struct GlobalLock;
+
+static OWNER: Mutex<Option<(ThreadId, usize)>> = Mutex::new(None);
+static RELEASED: Condvar = Condvar::new();
+
+impl GlobalLock {
+    pub(super) fn acquire() -> Self {
+        let me = std::thread::current().id();
+        let mut owner = OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            match owner.as_mut() {
+                None => {
+                    *owner = Some((me, 1));
+                    return GlobalLock;
+                }
+                Some((id, count)) if *id == me => {
+                    *count += 1;
+                    return GlobalLock;
+                }
+                Some(_) => {
+                    owner = RELEASED.wait(owner).unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for GlobalLock {
+    fn drop(&mut self) {
+        let mut owner = OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, count)) = owner.as_mut() {
+            *count -= 1;
+            if *count == 0 {
+                *owner = None;
+                RELEASED.notify_all();
+            }
+        }
+    }
+}
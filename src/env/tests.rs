@@ -7,6 +7,14 @@ use test_case::test_case;
     "!% SHOULD NOT EXIST %!"
     => err_str(r#"environment variable "!% SHOULD NOT EXIST %!": environment variable not found"#)
 )]
+#[test_case(
+    "BAD = SIGN"
+    => err_str(r#"environment variable "BAD = SIGN": environment variable contains '='"#)
+)]
+#[test_case(
+    ""
+    => err_str(r#"environment variable "": environment variable name is empty"#)
+)]
 fn var(key: &str) -> Result<String, String> {
     stringify_error(env::var(key))
 }
@@ -26,3 +34,422 @@ fn var(key: &str) -> Result<String, String> {
 fn var_os(key: &str) -> Result<OsString, String> {
     stringify_error(env::var_os(key))
 }
+
+#[test_case("ANYHOW_STD_TEST_VAR_PARSE_OK", Some("8080") => Ok(8080))]
+#[test_case(
+    "ANYHOW_STD_TEST_VAR_PARSE_ABSENT", None
+    => err_str(r#"environment variable "ANYHOW_STD_TEST_VAR_PARSE_ABSENT": environment variable not found"#)
+)]
+#[test_case(
+    "ANYHOW_STD_TEST_VAR_PARSE_INVALID", Some("80 80")
+    => err_str(r#"environment variable "ANYHOW_STD_TEST_VAR_PARSE_INVALID": while parsing "80 80" as u16: invalid digit found in string"#)
+)]
+fn var_parse(key: &str, value: Option<&str>) -> Result<u16, String> {
+    set_test_var(key, value);
+    stringify_error(env::var_parse::<u16, _>(key))
+}
+
+#[test_case("ANYHOW_STD_TEST_VAR_OR_PRESENT", Some("7") => Ok(7))]
+#[test_case("ANYHOW_STD_TEST_VAR_OR_ABSENT", None => Ok(42))]
+#[test_case(
+    "ANYHOW_STD_TEST_VAR_OR_INVALID", Some("-7")
+    => err_str(r#"environment variable "ANYHOW_STD_TEST_VAR_OR_INVALID": while parsing "-7" as u32: invalid digit found in string"#)
+)]
+fn var_or(key: &str, value: Option<&str>) -> Result<u32, String> {
+    set_test_var(key, value);
+    stringify_error(env::var_or(key, 42u32))
+}
+
+#[test_case("ANYHOW_STD_TEST_VAR_OPT_PRESENT", Some("x") => Ok(Some("x".to_string())))]
+#[test_case("ANYHOW_STD_TEST_VAR_OPT_ABSENT", None => Ok(None))]
+fn var_opt(key: &str, value: Option<&str>) -> Result<Option<String>, String> {
+    set_test_var(key, value);
+    stringify_error(env::var_opt(key))
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn var_opt_invalid_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let key = "ANYHOW_STD_TEST_VAR_OPT_INVALID_UTF8";
+    std::env::set_var(key, std::ffi::OsStr::from_bytes(b"bad \xff value"));
+    assert_eq!(
+        stringify_error(env::var_opt(key)),
+        err_str(
+            "environment variable \"ANYHOW_STD_TEST_VAR_OPT_INVALID_UTF8\": while processing os string \"bad \u{FFFD} value\": not valid utf8 at byte offset 4 (\\xFF)"
+        ),
+    );
+}
+
+#[test_case("ANYHOW_STD_TEST_VAR_BOOL_1", Some("1") => Ok(true))]
+#[test_case("ANYHOW_STD_TEST_VAR_BOOL_YES", Some("Yes") => Ok(true))]
+#[test_case("ANYHOW_STD_TEST_VAR_BOOL_OFF", Some("off") => Ok(false))]
+#[test_case(
+    "ANYHOW_STD_TEST_VAR_BOOL_INVALID", Some("maybe")
+    => err_str(r#"environment variable "ANYHOW_STD_TEST_VAR_BOOL_INVALID": while parsing "maybe" as bool: expected one of 1, true, yes, on, 0, false, no, off"#)
+)]
+fn var_bool(key: &str, value: Option<&str>) -> Result<bool, String> {
+    set_test_var(key, value);
+    stringify_error(env::var_bool(key))
+}
+
+fn set_test_var(key: &str, value: Option<&str>) {
+    match value {
+        Some(v) => std::env::set_var(key, v),
+        None => std::env::remove_var(key),
+    }
+}
+
+#[test]
+fn reader_collects_failures() {
+    std::env::set_var("ANYHOW_STD_TEST_READER_PORT", "eighty");
+    std::env::set_var("ANYHOW_STD_TEST_READER_DEBUG", "yes");
+    std::env::set_var("ANYHOW_STD_TEST_READER_NAME", "svc");
+
+    let mut reader = env::Reader::new();
+    let port = reader.var_parse::<u16, _>("ANYHOW_STD_TEST_READER_PORT");
+    let debug = reader.var_bool("ANYHOW_STD_TEST_READER_DEBUG");
+    let name = reader.var("ANYHOW_STD_TEST_READER_NAME");
+    let missing = reader.var_os("ANYHOW_STD_TEST_READER_MISSING");
+    let workers = reader.var_or("ANYHOW_STD_TEST_READER_WORKERS", 4usize);
+    let custom = reader.check(
+        "ANYHOW_STD_TEST_READER_NAME",
+        Err::<(), _>(anyhow::anyhow!("must be longer than 3 characters")),
+    );
+
+    assert_eq!(port, None);
+    assert_eq!(debug, Some(true));
+    assert_eq!(name, Some("svc".to_string()));
+    assert_eq!(missing, None);
+    assert_eq!(workers, Some(4));
+    assert_eq!(custom, None);
+    assert_eq!(
+        stringify_error(reader.finish()),
+        err_str(
+            r#"3 environment variable errors:
+- "ANYHOW_STD_TEST_READER_PORT": while parsing "eighty" as u16: invalid digit found in string
+- "ANYHOW_STD_TEST_READER_MISSING": environment variable not found
+- "ANYHOW_STD_TEST_READER_NAME": must be longer than 3 characters"#
+        ),
+    );
+}
+
+#[test]
+fn reader_without_failures() {
+    let mut reader = env::Reader::new();
+    assert_eq!(reader.var_opt("!% SHOULD NOT EXIST %!"), Some(None));
+    assert!(reader.finish().is_ok());
+}
+
+#[test]
+fn scoped_var_restores() -> anyhow::Result<()> {
+    let key = "ANYHOW_STD_TEST_SCOPED_VAR";
+    std::env::set_var(key, "original");
+    {
+        let _outer = env::ScopedVar::set(key, "outer")?;
+        assert_eq!(env::var(key)?, "outer");
+        {
+            let _inner = env::ScopedVar::remove(key)?;
+            assert_eq!(env::var_opt(key)?, None);
+        }
+        assert_eq!(env::var(key)?, "outer");
+    }
+    assert_eq!(env::var(key)?, "original");
+
+    std::env::remove_var(key);
+    {
+        let _guard = env::ScopedVar::set(key, "temporary")?;
+        assert_eq!(env::var(key)?, "temporary");
+    }
+    assert_eq!(env::var_opt(key)?, None);
+    Ok(())
+}
+
+#[test_case(
+    "BAD = SIGN", "value"
+    => err_str(r#"environment variable "BAD = SIGN": environment variable contains '='"#)
+)]
+#[test_case(
+    "ANYHOW_STD_TEST_SCOPED_VAR_NUL", "bad \0 value"
+    => err_str(r#"environment variable "ANYHOW_STD_TEST_SCOPED_VAR_NUL": environment variable value contains '\0'"#)
+)]
+fn scoped_var_set(key: &str, value: &str) -> Result<(), String> {
+    stringify_error(env::ScopedVar::set(key, value).map(|_| ()))
+}
+
+#[test]
+fn scoped_current_dir_restores() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let original = std::env::current_dir()?;
+    {
+        let _guard = env::ScopedCurrentDir::set(dir.path())?;
+        assert_eq!(std::env::current_dir()?, dir.path().canonicalize()?);
+    }
+    assert_eq!(std::env::current_dir()?, original);
+
+    assert_eq!(
+        stringify_error(env::ScopedCurrentDir::set("/this/path/should/not/exist").map(|_| ())),
+        err_str(
+            r#"while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+        ),
+    );
+    assert_eq!(std::env::current_dir()?, original);
+    Ok(())
+}
+
+#[test]
+fn set_and_remove_var() -> anyhow::Result<()> {
+    let key = "ANYHOW_STD_TEST_SET_VAR";
+    env::set_var(key, "value")?;
+    assert_eq!(env::var(key)?, "value");
+    env::remove_var(key)?;
+    assert_eq!(env::var_opt(key)?, None);
+    Ok(())
+}
+
+#[test_case(
+    "BAD = SIGN", "value"
+    => err_str(r#"environment variable "BAD = SIGN": environment variable contains '='"#)
+)]
+#[test_case(
+    "", "value"
+    => err_str(r#"environment variable "": environment variable name is empty"#)
+)]
+#[test_case(
+    "ANYHOW_STD_TEST_SET_VAR_NUL", "bad \0 value"
+    => err_str(r#"environment variable "ANYHOW_STD_TEST_SET_VAR_NUL": environment variable value contains '\0'"#)
+)]
+fn set_var(key: &str, value: &str) -> Result<(), String> {
+    stringify_error(env::set_var(key, value))
+}
+
+#[test_case(
+    "BAD \0 CHAR"
+    => err_str(r#"environment variable "BAD \0 CHAR": environment variable contains '\0'"#)
+)]
+fn remove_var(key: &str) -> Result<(), String> {
+    stringify_error(env::remove_var(key))
+}
+
+#[test]
+fn current_dir_and_exe() -> anyhow::Result<()> {
+    // Hold the lock of ScopedCurrentDir, so no other test changes directory meanwhile:
+    let _lock = super::scoped::GlobalLock::acquire();
+    assert_eq!(env::current_dir()?, std::env::current_dir()?);
+    assert_eq!(env::current_exe()?, std::env::current_exe()?);
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn home_dir() -> anyhow::Result<()> {
+    let _guard = env::ScopedVar::set("HOME", "/home/anyhow-std-test")?;
+    assert_eq!(
+        env::home_dir()?,
+        std::path::Path::new("/home/anyhow-std-test")
+    );
+    Ok(())
+}
+
+#[test]
+fn temp_dir() -> anyhow::Result<()> {
+    assert_eq!(env::temp_dir()?, std::env::temp_dir());
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn temp_dir_missing() {
+    // Rather than mutating TMPDIR, which every TempDir relies on, check the directory directly:
+    assert_eq!(
+        stringify_error(super::temp_dir_from("/this/path/should/not/exist".into())),
+        err_str(
+            r#"while locating the temporary directory: while processing path "/this/path/should/not/exist": No such file or directory (os error 2)"#
+        ),
+    );
+}
+
+#[test]
+fn args_utf8() -> anyhow::Result<()> {
+    assert_eq!(env::args_utf8()?, std::env::args().collect::<Vec<_>>());
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn args_utf8_invalid() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let args = vec![
+        OsString::from("prog"),
+        OsString::from("--name"),
+        std::ffi::OsStr::from_bytes(b"caf\xe9").to_os_string(),
+    ];
+    assert_eq!(
+        stringify_error(super::args_utf8_from(args)),
+        err_str(
+            "command line argument 2: while processing os string \"caf\u{FFFD}\": not valid utf8 at byte offset 3 (\\xE9)"
+        ),
+    );
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn split_paths_var() -> anyhow::Result<()> {
+    let key = "ANYHOW_STD_TEST_SPLIT_PATHS_VAR";
+    let _guard = env::ScopedVar::set(key, "/usr/lib:/opt/lib")?;
+    assert_eq!(
+        env::split_paths_var(key)?,
+        vec![
+            std::path::PathBuf::from("/usr/lib"),
+            std::path::PathBuf::from("/opt/lib"),
+        ],
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(&["/usr/bin", "/bin"] => Ok(OsString::from("/usr/bin:/bin")) ; "ok")]
+#[test_case(
+    &["/usr/bin", "/weird:dir", "/bin"]
+    => err_str(r#"path element 1 "/weird:dir": path segment contains separator `:`"#)
+    ; "separator"
+)]
+fn join_paths_anyhow(paths: &[&str]) -> Result<OsString, String> {
+    stringify_error(env::join_paths_anyhow(paths))
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn which() -> anyhow::Result<()> {
+    // Rather than mutating PATH, which other tests rely on, search a PATH-style value directly:
+    let dir = tempfile::TempDir::new()?;
+    let pathvar = env::join_paths_anyhow([
+        std::path::Path::new("/this/path/should/not/exist"),
+        dir.path(),
+    ])?;
+    let which = |program: &str| super::which_in(program.as_ref(), &pathvar);
+
+    assert_eq!(
+        stringify_error(which("anyhow-std-test-prog")),
+        Err(format!(
+            r#"program "anyhow-std-test-prog" not found in PATH directories ["/this/path/should/not/exist", {:?}]"#,
+            dir.path(),
+        )),
+    );
+
+    let prog = dir.path().join("anyhow-std-test-prog");
+    std::fs::write(&prog, "#!/bin/sh\n")?;
+    assert!(which("anyhow-std-test-prog").is_err());
+    assert_eq!(
+        stringify_error(env::which(&prog)),
+        Err(format!(
+            "while processing path {:?}: not an executable file",
+            prog.display()
+        )),
+    );
+
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(&prog, std::fs::Permissions::from_mode(0o755))?;
+    }
+    assert_eq!(which("anyhow-std-test-prog")?, prog);
+    assert_eq!(env::which(&prog)?, prog);
+    assert!(env::which("sh")?.ends_with("sh"));
+    Ok(())
+}
+
+#[test_case("plain text" => Ok("plain text".to_string()) ; "no references")]
+#[test_case(
+    "$ANYHOW_STD_TEST_EXPAND_DIR/cache" => Ok("/data/cache".to_string()) ; "bare"
+)]
+#[test_case(
+    "${ANYHOW_STD_TEST_EXPAND_DIR}x" => Ok("/datax".to_string()) ; "braced"
+)]
+#[test_case(
+    "${ANYHOW_STD_TEST_EXPAND_UNSET:-/var/lib/x}" => Ok("/var/lib/x".to_string()) ; "default"
+)]
+#[test_case(
+    "${ANYHOW_STD_TEST_EXPAND_UNSET:-${ANYHOW_STD_TEST_EXPAND_DIR}/y}"
+    => Ok("/data/y".to_string())
+    ; "nested default"
+)]
+#[test_case("$$5 costs $ 1$" => Ok("$5 costs $ 1$".to_string()) ; "literal dollars")]
+#[test_case(
+    "$ANYHOW_STD_TEST_EXPAND_DIR/$ANYHOW_STD_TEST_EXPAND_UNSET"
+    => err_str(r#"while expanding "$ANYHOW_STD_TEST_EXPAND_DIR/$ANYHOW_STD_TEST_EXPAND_UNSET": variable "ANYHOW_STD_TEST_EXPAND_UNSET" at column 29: environment variable not found"#)
+    ; "undefined"
+)]
+#[test_case(
+    "a ${ANYHOW_STD_TEST_EXPAND_DIR"
+    => err_str(r#"while expanding "a ${ANYHOW_STD_TEST_EXPAND_DIR": unterminated "${" at column 3"#)
+    ; "unterminated"
+)]
+#[test_case(
+    "${1x}"
+    => err_str(r#"while expanding "${1x}": invalid variable name at column 3"#)
+    ; "invalid name"
+)]
+#[test_case(
+    "${ANYHOW_STD_TEST_EXPAND_DIR#x}"
+    => err_str(r##"while expanding "${ANYHOW_STD_TEST_EXPAND_DIR#x}": unsupported expansion at column 29"##)
+    ; "unsupported"
+)]
+fn expand(template: &str) -> Result<String, String> {
+    let _dir = env::ScopedVar::set("ANYHOW_STD_TEST_EXPAND_DIR", "/data").unwrap();
+    let _unset = env::ScopedVar::remove("ANYHOW_STD_TEST_EXPAND_UNSET").unwrap();
+    stringify_error(env::expand(template))
+}
+
+#[test]
+fn vars_anyhow() -> anyhow::Result<()> {
+    let _guard = env::ScopedVar::set("ANYHOW_STD_TEST_VARS_ANYHOW", "present")?;
+    let found = env::vars_anyhow()
+        .filter_map(Result::ok)
+        .find(|(key, _)| key == "ANYHOW_STD_TEST_VARS_ANYHOW");
+    assert_eq!(
+        found,
+        Some((
+            "ANYHOW_STD_TEST_VARS_ANYHOW".to_string(),
+            "present".to_string()
+        ))
+    );
+    Ok(())
+}
+
+#[test]
+fn vars_with_prefix() -> anyhow::Result<()> {
+    let _a = env::ScopedVar::set("ANYHOW_STD_TEST_PREFIX_A", "1")?;
+    let _b = env::ScopedVar::set("ANYHOW_STD_TEST_PREFIX_B", "2")?;
+    assert_eq!(
+        env::vars_with_prefix("ANYHOW_STD_TEST_PREFIX_")?,
+        [("A", "1"), ("B", "2")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(b"APP_KEY", b"bad \xff value" => err_str(
+    "environment variable \"APP_KEY\": while processing os string \"bad \u{FFFD} value\": not valid utf8 at byte offset 4 (\\xFF)"
+) ; "invalid value")]
+#[test_case(b"APP_\xffKEY", b"value" => err_str(
+    "environment variable \"APP_\u{FFFD}KEY\": while processing os string \"APP_\u{FFFD}KEY\": not valid utf8 at byte offset 4 (\\xFF)"
+) ; "invalid key")]
+#[test_case(b"OTHER_\xffKEY", b"value" => Ok(1) ; "invalid key without prefix")]
+fn vars_with_prefix_invalid(key: &[u8], value: &[u8]) -> Result<usize, String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let vars = [
+        (OsString::from("APP_OK"), OsString::from("ok")),
+        (
+            std::ffi::OsStr::from_bytes(key).to_os_string(),
+            std::ffi::OsStr::from_bytes(value).to_os_string(),
+        ),
+    ];
+    stringify_error(super::vars_with_prefix_from(vars, "APP_").map(|m| m.len()))
+}
@@ -0,0 +1,127 @@
+//! Query errors for the io errors, paths, and commands described by this crate's contexts
+//!
+//! Paths and commands are attached by [PathAnyhow](crate::PathAnyhow),
+//! [CommandAnyhow](crate::CommandAnyhow), [ResultAnyhow](crate::ResultAnyhow), and
+//! [wrap_method](crate::wrap_method) as typed context values, so they can be recovered after an
+//! error has been given further context:
+//!
+//! ```
+//! use anyhow::Context;
+//! use anyhow_std::{error, PathAnyhow};
+//! use std::path::Path;
+//!
+//! let e = Path::new("/etc/this/path/should/not/exist")
+//!     .read_to_string_anyhow()
+//!     .context("while loading configuration")
+//!     .err()
+//!     .unwrap();
+//!
+//! assert!(error::is_not_found(&e));
+//! assert!(error::paths(&e).iter().any(|p| p.starts_with("/etc")));
+//! ```
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The kind of the first [std::io::Error] in the chain of `error`, if any
+pub fn io_error_kind(error: &anyhow::Error) -> Option<ErrorKind> {
+    error
+        .chain()
+        .find_map(|e| e.downcast_ref::<std::io::Error>())
+        .map(std::io::Error::kind)
+}
+
+/// Whether the chain of `error` contains a [std::io::Error] of kind [ErrorKind::NotFound]
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    io_error_kind(error) == Some(ErrorKind::NotFound)
+}
+
+/// Every path given as context to `error` by this crate, from the outermost context inwards
+///
+/// Paths given as a second argument, such as the destination of
+/// [PathAnyhow::copy_anyhow](crate::PathAnyhow::copy_anyhow), are included. Each path context
+/// records those beneath it when attached, so paths are found through any further contexts,
+/// while the error still downcasts to its original type.
+pub fn paths(error: &anyhow::Error) -> &[PathBuf] {
+    error
+        .downcast_ref::<PathContext>()
+        .map(|c| c.paths.as_slice())
+        .unwrap_or_default()
+}
+
+/// The outermost command given as context to `error` by this crate
+pub fn command(error: &anyhow::Error) -> Option<&CommandContext> {
+    error.downcast_ref::<CommandContext>()
+}
+
+/// The context attached for a path, found with [paths]
+#[derive(Clone, Debug)]
+pub struct PathContext {
+    description: String,
+    // The path of this context, followed by those of inner contexts:
+    paths: Vec<PathBuf>,
+}
+
+impl PathContext {
+    pub(crate) fn attach(error: anyhow::Error, prefix: Cow<'_, str>, path: &Path) -> anyhow::Error {
+        let mut paths = vec![path.to_path_buf()];
+        paths.extend(self::paths(&error).iter().cloned());
+        let description = format!("{} {}", prefix, crate::display::path_debug(path));
+        error.context(PathContext { description, paths })
+    }
+
+    /// The path described by this context
+    pub fn path(&self) -> &Path {
+        &self.paths[0]
+    }
+}
+
+impl fmt::Display for PathContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.description.fmt(f)
+    }
+}
+
+/// The context attached for a command, found with [command]
+#[derive(Clone, Debug)]
+pub struct CommandContext {
+    description: String,
+    program: OsString,
+    args: Vec<OsString>,
+}
+
+impl CommandContext {
+    pub(crate) fn attach(
+        error: anyhow::Error,
+        cmd: &Command,
+        description: String,
+    ) -> anyhow::Error {
+        error.context(CommandContext {
+            description,
+            program: cmd.get_program().to_os_string(),
+            args: cmd.get_args().map(|a| a.to_os_string()).collect(),
+        })
+    }
+
+    /// The program of the command
+    pub fn program(&self) -> &OsStr {
+        &self.program
+    }
+
+    /// The arguments of the command
+    pub fn args(&self) -> &[OsString] {
+        &self.args
+    }
+}
+
+impl fmt::Display for CommandContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.description.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,123 @@
+use crate::error::{command, io_error_kind, is_not_found, paths};
+use crate::{CommandAnyhow, PathAnyhow, ResultAnyhow};
+use anyhow::Context;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[test]
+fn io_error_kind_through_contexts() {
+    let e = Path::new("/this/path/should/not/exist")
+        .read_anyhow()
+        .context("while loading")
+        .context("while starting")
+        .err()
+        .unwrap();
+    assert_eq!(io_error_kind(&e), Some(ErrorKind::NotFound));
+    assert!(is_not_found(&e));
+}
+
+#[test]
+fn no_io_error() {
+    let e = Path::new("/").parent_anyhow().err().unwrap();
+    assert_eq!(io_error_kind(&e), None);
+    assert!(!is_not_found(&e));
+    assert_eq!(paths(&e), &[PathBuf::from("/")]);
+}
+
+#[test]
+fn paths_outermost_first() {
+    let e = Path::new("/src/a")
+        .copy_anyhow("/dst/b")
+        .context("while installing")
+        .with_path_context("/pkg")
+        .err()
+        .unwrap();
+    assert_eq!(
+        paths(&e),
+        &[
+            PathBuf::from("/pkg"),
+            PathBuf::from("/src/a"),
+            PathBuf::from("/dst/b"),
+        ]
+    );
+    assert_eq!(
+        format!("{:#}", e),
+        r#"while processing path "/pkg": while installing: while processing path "/src/a": with copy_to "/dst/b": No such file or directory (os error 2)"#
+    );
+}
+
+#[test]
+fn paths_of_write() {
+    let e = Path::new("/this/dir/should/not/exist/f")
+        .write_anyhow("x")
+        .err()
+        .unwrap();
+    assert_eq!(paths(&e), &[PathBuf::from("/this/dir/should/not/exist/f")]);
+    assert!(is_not_found(&e));
+}
+
+#[test]
+fn no_paths() {
+    let e = anyhow::anyhow!("plain");
+    assert!(paths(&e).is_empty());
+    assert!(command(&e).is_none());
+}
+
+#[test]
+fn command_context() {
+    let mut cmd = Command::new("!-we-assume-this-program-does-not-exist-!");
+    cmd.args(["-v", "x"]);
+    let e = cmd.status_anyhow().context("while checking").err().unwrap();
+    let ctx = command(&e).unwrap();
+    assert_eq!(ctx.program(), "!-we-assume-this-program-does-not-exist-!");
+    assert_eq!(ctx.args(), &["-v", "x"]);
+    assert!(is_not_found(&e));
+}
+
+#[test]
+fn contexts_beneath_other_contexts() {
+    let e = Path::new("/this/path/should/not/exist")
+        .read_anyhow()
+        .with_context(|| "while loading")
+        .with_path_arg_context("backup", "/backup")
+        .context("while restoring")
+        .with_command_context(&Command::new("restore"))
+        .context("while starting")
+        .err()
+        .unwrap();
+    assert_eq!(
+        paths(&e),
+        &[
+            PathBuf::from("/backup"),
+            PathBuf::from("/this/path/should/not/exist"),
+        ]
+    );
+    assert_eq!(command(&e).unwrap().program(), "restore");
+    assert!(is_not_found(&e));
+}
+
+#[test]
+fn downcasts_through_contexts() {
+    let e = Path::new("/this/path/should/not/exist")
+        .read_anyhow()
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.downcast_ref::<std::io::Error>().map(std::io::Error::kind),
+        Some(ErrorKind::NotFound)
+    );
+    assert_eq!(
+        e.downcast::<std::io::Error>().ok().map(|e| e.kind()),
+        Some(ErrorKind::NotFound)
+    );
+
+    let e = Command::new("!-we-assume-this-program-does-not-exist-!")
+        .status_anyhow()
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.downcast_ref::<std::io::Error>().map(std::io::Error::kind),
+        Some(ErrorKind::NotFound)
+    );
+}
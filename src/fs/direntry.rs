@@ -1,5 +1,5 @@
 use crate::fs::Metadata;
-use anyhow::Context;
+use crate::ResultAnyhow;
 use std::fs::FileType;
 use std::ops::Deref;
 
@@ -15,14 +15,12 @@ impl DirEntry {
         self.de
             .metadata()
             .map(|md| Metadata::from((md, self.path())))
-            .with_context(|| format!("while processing path {:?}", self.path().display()))
+            .with_path_context(self.path())
     }
 
     /// Extend [std::fs::DirEntry::file_type] providing the path in the error context
     pub fn file_type(&self) -> anyhow::Result<FileType> {
-        self.de
-            .file_type()
-            .with_context(|| format!("while processing path {:?}", self.path().display()))
+        self.de.file_type().with_path_context(self.path())
     }
 }
 
@@ -1,7 +1,9 @@
+use crate::ResultAnyhow;
+use crate::SystemTimeAnyhow;
 use anyhow::Context;
 use std::ops::Deref;
 use std::path::PathBuf;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// Wraps [std::fs::Metadata] to provide the path as error context
 #[derive(Debug, derive_more::From, derive_more::Into)]
@@ -13,23 +15,38 @@ pub struct Metadata {
 impl Metadata {
     /// Extend [std::fs::Metadata::modified] to provide the path as error context
     pub fn modified(&self) -> anyhow::Result<SystemTime> {
-        self.md
-            .modified()
-            .with_context(|| format!("while processing path {:?}", self.path.display()))
+        self.md.modified().with_path_context(&self.path)
     }
 
     /// Extend [std::fs::Metadata::accessed] to provide the path as error context
     pub fn accessed(&self) -> anyhow::Result<SystemTime> {
-        self.md
-            .accessed()
-            .with_context(|| format!("while processing path {:?}", self.path.display()))
+        self.md.accessed().with_path_context(&self.path)
     }
 
     /// Extend [std::fs::Metadata::created] to provide the path as error context
     pub fn created(&self) -> anyhow::Result<SystemTime> {
+        self.md.created().with_path_context(&self.path)
+    }
+
+    /// Wrap [SystemTimeAnyhow::elapsed_anyhow] for [Metadata::modified], providing the path as error context
+    pub fn modified_elapsed(&self) -> anyhow::Result<Duration> {
+        self.modified_and_then(|t| t.elapsed_anyhow())
+    }
+
+    /// Wrap [SystemTimeAnyhow::duration_since_anyhow] for [Metadata::modified], providing the path as error context
+    pub fn modified_since(&self, earlier: SystemTime) -> anyhow::Result<Duration> {
+        self.modified_and_then(|t| t.duration_since_anyhow(earlier))
+    }
+
+    fn modified_and_then<F>(&self, f: F) -> anyhow::Result<Duration>
+    where
+        F: FnOnce(SystemTime) -> anyhow::Result<Duration>,
+    {
         self.md
-            .created()
-            .with_context(|| format!("while processing path {:?}", self.path.display()))
+            .modified()
+            .map_err(anyhow::Error::from)
+            .and_then(|t| f(t).context("modified time"))
+            .with_path_context(&self.path)
     }
 }
 
@@ -40,3 +57,6 @@ impl Deref for Metadata {
         &self.md
     }
 }
+
+#[cfg(test)]
+mod tests;
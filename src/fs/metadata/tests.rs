@@ -0,0 +1,40 @@
+use crate::testutils::{assert_error_desc_eq, stringify_error};
+use crate::PathAnyhow;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn modified_in_future() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("future");
+    let requested = SystemTime::now() + Duration::from_secs(3600);
+    std::fs::File::create(&path)?.set_modified(requested)?;
+    let md = path.metadata_anyhow()?;
+
+    // Filesystems store timestamps with varying precision, so compare against the stored time:
+    let modified = md.modified()?;
+    let skew = requested
+        .duration_since(modified)
+        .unwrap_or_else(|e| e.duration());
+    assert!(skew < Duration::from_secs(2), "{:?}", skew);
+
+    assert_eq!(
+        stringify_error(md.modified_since(modified - Duration::from_secs(5))),
+        Ok(Duration::from_secs(5))
+    );
+    assert_error_desc_eq(
+        md.modified_since(modified + Duration::from_millis(1500)),
+        &format!(
+            "while processing path {:?}: modified time: time is 1.5s before the earlier time",
+            path.display()
+        ),
+    );
+
+    let desc = format!("{:#}", md.modified_elapsed().err().unwrap());
+    let prefix = format!(
+        "while processing path {:?}: modified time: time is ",
+        path.display()
+    );
+    assert!(desc.starts_with(&prefix), "{:?}", desc);
+    assert!(desc.ends_with(" in the future"), "{:?}", desc);
+    Ok(())
+}
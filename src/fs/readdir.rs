@@ -1,5 +1,6 @@
+use crate::error::PathContext;
 use crate::fs::DirEntry;
-use anyhow::Context;
+use crate::ResultAnyhow;
 use std::path::{Path, PathBuf};
 
 /// Wraps [std::fs::ReadDir] to provide the directory as error context
@@ -33,7 +34,8 @@ fn wrap_read_dir_item(
     item.map(|stditem| {
         stditem
             .map(DirEntry::from)
-            .with_context(|| format!("while reading directory {:?}", path.display()))
+            .map_err(|e| PathContext::attach(e.into(), "while reading directory".into(), path))
+            .with_scope_context()
     })
 }
 
@@ -1,14 +1,31 @@
 #![doc = include_str!("../README.md")]
 
+pub mod context;
+mod convert;
+pub mod display;
 pub mod env;
+pub mod error;
 pub mod fs;
 mod osstr;
 mod path;
 pub mod process;
+mod strext;
+mod time;
+mod wrapmacro;
 
-pub use self::osstr::OsStrAnyhow;
+pub use self::context::{AnyhowContext, ContextDisplay, ResultAnyhow};
+pub use self::convert::TryIntoAnyhow;
+pub use self::osstr::{OsStrAnyhow, OsStringAnyhow};
 pub use self::path::PathAnyhow;
 pub use self::process::CommandAnyhow;
+pub use self::strext::StrAnyhow;
+pub use self::time::SystemTimeAnyhow;
+
+#[cfg(feature = "macros")]
+pub use anyhow_std_macros::anyhow_context;
+
+#[doc(hidden)]
+pub use anyhow as __anyhow;
 
 #[cfg(test)]
 pub mod testutils;
@@ -1,44 +1,150 @@
+use crate::AnyhowContext;
+use crate::ResultAnyhow;
+use crate::StrAnyhow;
 use anyhow::Context;
-use std::borrow::Cow;
-use std::ffi::OsStr;
-
-const LONG_STRING_DISPLAY_LIMIT: usize = 80;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// Extend [std::ffi::OsStr] with [anyhow] methods
 pub trait OsStrAnyhow {
     /// Produce a utf8 `&str` or provide an error describing the string, truncated if long
+    ///
+    /// On unix, the error also gives the byte offset of the first invalid sequence, along with
+    /// its raw bytes in hex, which are otherwise lost in the lossy rendering of the string.
     fn to_str_anyhow(&self) -> anyhow::Result<&str>;
+
+    /// Parse a utf8 string into `T`, providing the string and target type as error context
+    fn parse_anyhow<T>(&self) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static;
+
+    /// Strip the utf8 `prefix`, or provide an error describing the string if it is missing
+    fn strip_prefix_anyhow(&self, prefix: &str) -> anyhow::Result<&OsStr>;
+
+    /// Split around the first occurrence of `delimiter`, or provide an error describing the string if it is missing
+    ///
+    /// The string need not be utf8 on either side of `delimiter`.
+    fn split_once_anyhow(&self, delimiter: char) -> anyhow::Result<(&OsStr, &OsStr)>;
+
+    /// Borrow as a [Path], or provide an error describing the string if no file could have that path
+    ///
+    /// Empty strings and strings containing a nul byte are rejected, since the operating system
+    /// would reject them only once the path is used.
+    fn to_path_anyhow(&self) -> anyhow::Result<&Path>;
 }
 
 impl OsStrAnyhow for OsStr {
     fn to_str_anyhow(&self) -> anyhow::Result<&str> {
         self.to_str()
-            .ok_or_else(|| anyhow::Error::msg("not valid utf8"))
-            .with_context(|| {
-                format!(
-                    "while processing os string {:?}",
-                    truncate_long_strings(self.to_string_lossy())
+            .ok_or_else(|| anyhow::Error::msg(invalid_utf8_description(self)))
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+
+    fn parse_anyhow<T>(&self) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.to_str_anyhow()?.parse_anyhow()
+    }
+
+    fn strip_prefix_anyhow(&self, prefix: &str) -> anyhow::Result<&OsStr> {
+        self.as_encoded_bytes()
+            .strip_prefix(prefix.as_bytes())
+            // SAFETY: splitting directly after a valid utf8 substring is permitted:
+            .map(|rest| unsafe { OsStr::from_encoded_bytes_unchecked(rest) })
+            .ok_or_else(|| anyhow::anyhow!("missing expected prefix {:?}", prefix))
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+
+    fn split_once_anyhow(&self, delimiter: char) -> anyhow::Result<(&OsStr, &OsStr)> {
+        let mut buf = [0; 4];
+        let delim = delimiter.encode_utf8(&mut buf).as_bytes();
+        let bytes = self.as_encoded_bytes();
+        bytes
+            .windows(delim.len())
+            .position(|w| w == delim)
+            // SAFETY: splitting directly around a valid utf8 substring is permitted:
+            .map(|i| unsafe {
+                (
+                    OsStr::from_encoded_bytes_unchecked(&bytes[..i]),
+                    OsStr::from_encoded_bytes_unchecked(&bytes[i + delim.len()..]),
                 )
             })
+            .ok_or_else(|| anyhow::anyhow!("missing expected delimiter {:?}", delimiter))
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+
+    fn to_path_anyhow(&self) -> anyhow::Result<&Path> {
+        let bytes = self.as_encoded_bytes();
+        let res = if bytes.is_empty() {
+            Err(anyhow::anyhow!("empty path"))
+        } else if let Some(i) = bytes.iter().position(|&b| b == 0) {
+            Err(anyhow::anyhow!(
+                "path contains a nul byte at byte offset {}",
+                i
+            ))
+        } else {
+            Ok(Path::new(self))
+        };
+        res.with_context(|| self.anyhow_context())
+            .with_scope_context()
     }
 }
 
-fn truncate_long_strings(s: Cow<'_, str>) -> Cow<'_, str> {
-    let sref = s.as_ref();
-    let charcnt = sref.chars().count();
+/// Extend [std::ffi::OsString] with [anyhow] methods
+pub trait OsStringAnyhow {
+    /// Convert into a utf8 [String] without reallocating, or provide an error describing the string, truncated if long
+    fn into_string_anyhow(self) -> anyhow::Result<String>;
+
+    /// Convert into a [PathBuf] without reallocating, rejecting strings as [OsStrAnyhow::to_path_anyhow] does
+    fn into_path_buf_anyhow(self) -> anyhow::Result<PathBuf>;
+}
 
-    if charcnt <= LONG_STRING_DISPLAY_LIMIT {
-        s
-    } else {
-        const HALF: usize = LONG_STRING_DISPLAY_LIMIT / 2;
+impl OsStringAnyhow for OsString {
+    fn into_string_anyhow(self) -> anyhow::Result<String> {
+        self.into_string()
+            .or_else(|os| os.to_str_anyhow().map(String::from))
+    }
 
-        dbg!(Cow::from(format!(
-            "{}\u{2772}\u{2026}\u{2773}{}",
-            sref.chars().take(HALF).collect::<String>(),
-            sref.chars().skip(charcnt - HALF + 3).collect::<String>(),
-        )))
+    fn into_path_buf_anyhow(self) -> anyhow::Result<PathBuf> {
+        self.to_path_anyhow()?;
+        Ok(PathBuf::from(self))
     }
 }
 
+#[cfg(target_family = "unix")]
+fn invalid_utf8_description(os: &OsStr) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let bytes = os.as_bytes();
+    match std::str::from_utf8(bytes) {
+        Ok(_) => "not valid utf8".to_string(),
+        Err(e) => {
+            let start = e.valid_up_to();
+            // A missing error length indicates a truncated sequence at the end:
+            let end = e.error_len().map_or(bytes.len(), |len| start + len);
+            format!(
+                "not valid utf8 at byte offset {} ({})",
+                start,
+                bytes[start..end]
+                    .iter()
+                    .map(|b| format!("\\x{:02X}", b))
+                    .collect::<String>()
+            )
+        }
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn invalid_utf8_description(_: &OsStr) -> String {
+    "not valid utf8".to_string()
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,5 +1,7 @@
-use crate::OsStrAnyhow;
-use std::ffi::OsStr;
+use crate::testutils::{err_str, stringify_error};
+use crate::{OsStrAnyhow, OsStringAnyhow};
+use std::ffi::{OsStr, OsString};
+use test_case::test_case;
 
 #[test]
 fn to_str_utf8() -> anyhow::Result<()> {
@@ -18,7 +20,7 @@ fn to_str_invalid_utf8() -> anyhow::Result<()> {
     let s = OsStr::from_bytes(b"invalid \xff utf8");
     let errdesc = format!("{:#}", s.to_str_anyhow().err().unwrap());
     assert_eq!(
-        "while processing os string \"invalid \u{FFFD} utf8\": not valid utf8",
+        "while processing os string \"invalid \u{FFFD} utf8\": not valid utf8 at byte offset 8 (\\xFF)",
         errdesc
     );
     Ok(())
@@ -26,6 +28,7 @@ fn to_str_invalid_utf8() -> anyhow::Result<()> {
 
 #[cfg(target_family = "unix")]
 #[test]
+#[allow(clippy::same_item_push)]
 fn to_str_invalid_utf8_big() -> anyhow::Result<()> {
     use std::os::unix::ffi::OsStrExt;
 
@@ -39,8 +42,110 @@ fn to_str_invalid_utf8_big() -> anyhow::Result<()> {
     let s = OsStr::from_bytes(v.as_slice());
     let errdesc = format!("{:#}", s.to_str_anyhow().err().unwrap());
     assert_eq!(
-        "while processing os string \"invalid utf8 consisting of these \u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{2772}\u{2026}\u{2773}tes, which is also a very long string\": not valid utf8",
+        "while processing os string \"invalid utf8 consisting of these \u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{2772}\u{2026}\u{2773}tes, which is also a very long string\": not valid utf8 at byte offset 33 (\\xFF)",
         errdesc
     );
     Ok(())
 }
+
+#[cfg(target_family = "unix")]
+#[test_case(b"caf\xe9 cr\xe8me" => "not valid utf8 at byte offset 3 (\\xE9)" ; "latin1")]
+#[test_case(b"euro \xe2\x82" => "not valid utf8 at byte offset 5 (\\xE2\\x82)" ; "truncated sequence")]
+#[test_case(b"\xed\xa0\x80 surrogate" => "not valid utf8 at byte offset 0 (\\xED)" ; "surrogate")]
+fn to_str_invalid_utf8_offset(bytes: &[u8]) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    format!(
+        "{}",
+        OsStr::from_bytes(bytes)
+            .to_str_anyhow()
+            .err()
+            .unwrap()
+            .root_cause()
+    )
+}
+
+#[test]
+fn into_string_utf8() -> anyhow::Result<()> {
+    let os = OsString::from("hello world! 😀");
+    let ptr = os.as_encoded_bytes().as_ptr();
+    let s = os.into_string_anyhow()?;
+    assert_eq!("hello world! 😀", s);
+    assert_eq!(ptr, s.as_ptr());
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn into_string_invalid_utf8() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let os = OsString::from_vec(b"invalid \xff utf8".to_vec());
+    assert_eq!(
+        "while processing os string \"invalid \u{FFFD} utf8\": not valid utf8 at byte offset 8 (\\xFF)",
+        format!("{:#}", os.into_string_anyhow().err().unwrap())
+    );
+}
+
+#[test_case("42" => Ok(42))]
+#[test_case("forty-two" => err_str(r#"while parsing "forty-two" as u16: invalid digit found in string"#))]
+#[test_case("70000" => err_str(r#"while parsing "70000" as u16: number too large to fit in target type"#))]
+fn parse(input: &str) -> Result<u16, String> {
+    stringify_error(OsStr::new(input).parse_anyhow())
+}
+
+#[test_case("key=value", "key" => Ok("=value".to_string()))]
+#[test_case("key=value", "value" => err_str(r#"while processing os string "key=value": missing expected prefix "value""#))]
+fn strip_prefix(input: &str, prefix: &str) -> Result<String, String> {
+    stringify_error(OsStr::new(input).strip_prefix_anyhow(prefix))
+        .map(|rest| rest.to_string_lossy().into_owned())
+}
+
+#[test_case("key=value=more", '=' => Ok(("key".to_string(), "value=more".to_string())))]
+#[test_case("key→value", '→' => Ok(("key".to_string(), "value".to_string())))]
+#[test_case("key", '=' => err_str(r#"while processing os string "key": missing expected delimiter '='"#))]
+fn split_once(input: &str, delimiter: char) -> Result<(String, String), String> {
+    stringify_error(OsStr::new(input).split_once_anyhow(delimiter)).map(|(a, b)| {
+        (
+            a.to_string_lossy().into_owned(),
+            b.to_string_lossy().into_owned(),
+        )
+    })
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn split_once_invalid_utf8() -> anyhow::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let (key, value) = OsStr::from_bytes(b"k\xffy=v\xfe").split_once_anyhow('=')?;
+    assert_eq!(
+        (key.as_bytes(), value.as_bytes()),
+        (&b"k\xffy"[..], &b"v\xfe"[..])
+    );
+    Ok(())
+}
+
+#[test_case("/etc/hosts" => Ok("/etc/hosts".to_string()) ; "ok")]
+#[test_case("" => err_str(r#"while processing os string "": empty path"#) ; "empty")]
+#[test_case("/etc/\0hosts" => err_str(r#"while processing os string "/etc/\0hosts": path contains a nul byte at byte offset 5"#) ; "nul byte")]
+fn to_path(input: &str) -> Result<String, String> {
+    stringify_error(
+        OsStr::new(input)
+            .to_path_anyhow()
+            .map(|p| p.display().to_string()),
+    )
+}
+
+#[test]
+fn into_path_buf() -> anyhow::Result<()> {
+    assert_eq!(
+        OsString::from("/etc/hosts").into_path_buf_anyhow()?,
+        std::path::Path::new("/etc/hosts"),
+    );
+    assert_eq!(
+        stringify_error(OsString::new().into_path_buf_anyhow()),
+        err_str(r#"while processing os string "": empty path"#),
+    );
+    Ok(())
+}
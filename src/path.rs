@@ -1,6 +1,7 @@
+use crate::error::PathContext;
 use crate::fs::Metadata;
 use crate::fs::ReadDir;
-use anyhow::Context;
+use crate::{wrap_method, ResultAnyhow};
 use std::ffi::OsStr;
 use std::fs::{File, Permissions};
 use std::path::{Path, PathBuf};
@@ -42,6 +43,9 @@ pub trait PathAnyhow {
     /// Wrap [Path::read_dir], providing the path as error context
     fn read_dir_anyhow(&self) -> anyhow::Result<ReadDir>;
 
+    /// Expand a leading `~` and environment variables as with [crate::env::expand], providing the path as error context
+    fn expand_anyhow(&self) -> anyhow::Result<PathBuf>;
+
     // Wrappers for std::fs:
 
     /// Wrap [std::fs::copy] from `self` to `to`, providing `self` and `to` as error context
@@ -105,36 +109,6 @@ pub trait PathAnyhow {
     fn create_file_anyhow(&self) -> anyhow::Result<File>;
 }
 
-macro_rules! wrap_method {
-    ( $method:ident, $cb:expr, $ret:ty, None: $errordesc:expr ) => {
-        fn $method(&self) -> anyhow::Result<$ret> {
-            let p = self.as_ref();
-            $cb(p)
-                .ok_or_else(|| anyhow::Error::msg($errordesc))
-                .with_context(|| format!("while processing path {:?}", p.display()))
-        }
-    };
-
-    ( $method:ident, $cb:expr, $ret:ty ) => {
-        #[allow(clippy::redundant_closure_call)]
-        fn $method(&self) -> anyhow::Result<$ret> {
-            $cb(self).with_context(|| format!("while processing path {:?}", self.display()))
-        }
-    };
-
-    ( $method:ident, $cb:expr, AsRefPath: $arg:ident, $ret:ty ) => {
-        fn $method<Q>(&self, $arg: Q) -> anyhow::Result<$ret>
-        where
-            Q: AsRef<Path>,
-        {
-            let argref = $arg.as_ref();
-            $cb(self, argref)
-                .with_context(|| format!("with {} {:?}", stringify!($arg), argref.display()))
-                .with_context(|| format!("while processing path {:?}", self.display()))
-        }
-    };
-}
-
 impl PathAnyhow for Path {
     wrap_method!(to_str_anyhow, Path::to_str, &str, None: "invalid UTF8");
 
@@ -188,6 +162,12 @@ impl PathAnyhow for Path {
     wrap_method!(canonicalize_anyhow, Path::canonicalize, PathBuf);
     wrap_method!(read_link_anyhow, Path::read_link, PathBuf);
     wrap_method!(read_dir_anyhow, ReadDir::from_path, ReadDir);
+
+    fn expand_anyhow(&self) -> anyhow::Result<PathBuf> {
+        let s = self.to_str_anyhow()?;
+        crate::env::expand_path(s).with_path_context(self)
+    }
+
     wrap_method!(copy_anyhow, std::fs::copy, AsRefPath: copy_to, u64);
     wrap_method!(create_dir_anyhow, std::fs::create_dir, ());
     wrap_method!(create_dir_all_anyhow, std::fs::create_dir_all, ());
@@ -199,11 +179,12 @@ impl PathAnyhow for Path {
     wrap_method!(remove_file_anyhow, std::fs::remove_file, ());
     wrap_method!(rename_anyhow, std::fs::rename, AsRefPath: rename_to, ());
 
-    fn set_permissions_anyhow(&self, perms: Permissions) -> anyhow::Result<()> {
-        std::fs::set_permissions(self, perms.clone())
-            .with_context(|| format!("with permissions {:?}", perms))
-            .with_context(|| format!("while processing path {:?}", self.display()))
-    }
+    wrap_method!(
+        set_permissions_anyhow,
+        std::fs::set_permissions,
+        Args: (permissions: Permissions),
+        ()
+    );
 
     fn set_readonly_anyhow(&self, readonly: bool) -> anyhow::Result<()> {
         let mut perms = self.metadata_anyhow()?.permissions();
@@ -216,7 +197,8 @@ impl PathAnyhow for Path {
         C: AsRef<[u8]>,
     {
         std::fs::write(self, contents)
-            .with_context(|| format!("while writing to {:?}", self.display()))
+            .map_err(|e| PathContext::attach(e.into(), "while writing to".into(), self))
+            .with_scope_context()
     }
 
     wrap_method!(set_to_current_dir_anyhow, std::env::set_current_dir, ());
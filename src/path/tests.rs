@@ -428,3 +428,24 @@ fn open_file(input: &str) -> Result<(), String> {
 fn create_file(input: &str) -> Result<(), String> {
     stringify_error(Path::new(input).create_file_anyhow().map(|_| ()))
 }
+
+#[test_case("~" => Ok("/home/anyhow-std-test".to_string()) ; "home")]
+#[test_case("~/cache/$ANYHOW_STD_TEST_PATH_EXPAND" => Ok("/home/anyhow-std-test/cache/app".to_string()) ; "home and var")]
+#[test_case("/srv/${ANYHOW_STD_TEST_PATH_EXPAND}/~" => Ok("/srv/app/~".to_string()) ; "tilde not leading")]
+#[test_case(
+    "~/$ANYHOW_STD_TEST_PATH_EXPAND_UNSET"
+    => err_str(r#"while processing path "~/$ANYHOW_STD_TEST_PATH_EXPAND_UNSET": while expanding "~/$ANYHOW_STD_TEST_PATH_EXPAND_UNSET": variable "ANYHOW_STD_TEST_PATH_EXPAND_UNSET" at column 3: environment variable not found"#)
+    ; "undefined"
+)]
+fn expand(input: &str) -> Result<String, String> {
+    use crate::env::ScopedVar;
+
+    let _home = ScopedVar::set("HOME", "/home/anyhow-std-test").unwrap();
+    let _var = ScopedVar::set("ANYHOW_STD_TEST_PATH_EXPAND", "app").unwrap();
+    let _unset = ScopedVar::remove("ANYHOW_STD_TEST_PATH_EXPAND_UNSET").unwrap();
+    stringify_error(
+        Path::new(input)
+            .expand_anyhow()
+            .and_then(|p| p.to_str_anyhow().map(String::from)),
+    )
+}
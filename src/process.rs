@@ -1,14 +1,21 @@
 //! Wrappers for [std::process] which provide commands in error contexts
 
+mod builder;
 mod child;
+mod cmdmacro;
 mod command;
 mod exitstatus;
 mod output;
+mod runner;
+mod shellwords;
 
+pub use self::builder::CommandBuilder;
 pub use self::child::Child;
 pub use self::command::CommandAnyhow;
 pub use self::exitstatus::ExitStatus;
 pub use self::output::Output;
+pub use self::runner::{CommandRunner, FakeRunner, SystemRunner};
+pub use self::shellwords::command_from_shell_str;
 
 #[cfg(test)]
 mod tests;
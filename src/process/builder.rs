@@ -0,0 +1,263 @@
+use crate::display::path_debug;
+use crate::process::command::{self, Setup};
+use crate::process::{Child, ExitStatus, Output};
+use crate::{PathAnyhow, ResultAnyhow};
+use anyhow::Context;
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Wrap [std::process::Command] to record setup which it does not expose, for error contexts
+///
+/// Stdio redirections to and from paths are described by [CommandBuilder::anyhow_context] in
+/// shell syntax, and the privileged setup of [CommandBuilder::uid], [CommandBuilder::gid], and
+/// [CommandBuilder::process_group] is named when spawning fails for lack of permission, as is a
+/// failed [CommandBuilder::pre_exec_anyhow] hook. All other
+/// configuration is available through [DerefMut] to the wrapped [Command]:
+///
+/// ```
+/// use anyhow_std::process::CommandBuilder;
+///
+/// let dir = tempfile::TempDir::new()?;
+/// let log = dir.path().join("log");
+///
+/// let mut cmd = CommandBuilder::new("/! no such program !/");
+/// cmd.arg("-v");
+/// cmd.stdout_to_path_anyhow(&log)?;
+///
+/// assert_eq!(
+///     cmd.anyhow_context(),
+///     format!(r#"command: "/! no such program !/" "-v" > {:?}"#, log),
+/// );
+/// assert!(cmd.status_anyhow().is_err());
+/// # anyhow::Ok(())
+/// ```
+///
+/// The setup is only recorded by the builder itself, so it is not described when:
+///
+/// - the wrapped [Command] is run by other means, such as through a
+///   [CommandRunner](crate::process::CommandRunner), which takes `&mut Command`;
+/// - stdio is reconfigured on the wrapped [Command] directly, as with `(*cmd).stdout(..)`,
+///   rather than with [CommandBuilder::stdin], [CommandBuilder::stdout], or
+///   [CommandBuilder::stderr], which forget the replaced redirection.
+#[derive(Debug)]
+pub struct CommandBuilder {
+    cmd: Command,
+    setup: Setup,
+}
+
+impl From<Command> for CommandBuilder {
+    fn from(cmd: Command) -> Self {
+        CommandBuilder {
+            cmd,
+            setup: Setup::default(),
+        }
+    }
+}
+
+impl Deref for CommandBuilder {
+    type Target = Command;
+
+    fn deref(&self) -> &Command {
+        &self.cmd
+    }
+}
+
+impl DerefMut for CommandBuilder {
+    fn deref_mut(&mut self) -> &mut Command {
+        &mut self.cmd
+    }
+}
+
+impl CommandBuilder {
+    /// Construct a [CommandBuilder] for `program` as with [Command::new]
+    pub fn new<S>(program: S) -> Self
+    where
+        S: AsRef<OsStr>,
+    {
+        CommandBuilder::from(Command::new(program))
+    }
+
+    /// Wrap [CommandExt::uid](std::os::unix::process::CommandExt::uid), recording it for error diagnosis
+    #[cfg(target_family = "unix")]
+    pub fn uid(&mut self, id: u32) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+
+        self.cmd.uid(id);
+        self.setup.privileged.push(format!("uid {}", id));
+        self
+    }
+
+    /// Wrap [CommandExt::gid](std::os::unix::process::CommandExt::gid), recording it for error diagnosis
+    #[cfg(target_family = "unix")]
+    pub fn gid(&mut self, id: u32) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+
+        self.cmd.gid(id);
+        self.setup.privileged.push(format!("gid {}", id));
+        self
+    }
+
+    /// Wrap [CommandExt::process_group](std::os::unix::process::CommandExt::process_group), recording it for error diagnosis
+    #[cfg(target_family = "unix")]
+    pub fn process_group(&mut self, pgroup: i32) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+
+        self.cmd.process_group(pgroup);
+        self.setup
+            .privileged
+            .push(format!("process group {}", pgroup));
+        self
+    }
+
+    /// Override [CommandAnyhow::pre_exec_anyhow](crate::CommandAnyhow::pre_exec_anyhow), recording the hook for error diagnosis
+    ///
+    /// When the hook fails in a spawned child, spawning fails with `pre_exec hook failed` as
+    /// context.
+    ///
+    /// # Safety
+    ///
+    /// This has the same safety requirements as [CommandExt::pre_exec](std::os::unix::process::CommandExt::pre_exec).
+    #[cfg(target_family = "unix")]
+    pub unsafe fn pre_exec_anyhow<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnMut() -> anyhow::Result<()> + Send + Sync + 'static,
+    {
+        use crate::CommandAnyhow;
+
+        // SAFETY: The caller upholds the requirements of `pre_exec`:
+        unsafe { self.cmd.pre_exec_anyhow(f) };
+        self.setup.pre_exec_hooks += 1;
+        self
+    }
+
+    /// Wrap [Command::stdin], forgetting any redirection recorded by [CommandBuilder::stdin_from_path_anyhow]
+    pub fn stdin<T>(&mut self, cfg: T) -> &mut Self
+    where
+        T: Into<Stdio>,
+    {
+        self.cmd.stdin(cfg);
+        self.setup.stdin = None;
+        self
+    }
+
+    /// Wrap [Command::stdout], forgetting any redirection recorded for stdout
+    pub fn stdout<T>(&mut self, cfg: T) -> &mut Self
+    where
+        T: Into<Stdio>,
+    {
+        self.cmd.stdout(cfg);
+        self.setup.stdout = None;
+        self
+    }
+
+    /// Wrap [Command::stderr], forgetting any redirection recorded for stderr
+    pub fn stderr<T>(&mut self, cfg: T) -> &mut Self
+    where
+        T: Into<Stdio>,
+    {
+        self.cmd.stderr(cfg);
+        self.setup.stderr = None;
+        self
+    }
+
+    /// Redirect stdout to a file created at `path`, providing the path as error context
+    pub fn stdout_to_path_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = self.open_redirect("stdout", path, |p| p.create_file_anyhow())?;
+        self.cmd.stdout(f);
+        self.setup.stdout = Some(format!("> {}", path_debug(path)));
+        Ok(self)
+    }
+
+    /// Redirect stderr to a file created at `path`, providing the path as error context
+    pub fn stderr_to_path_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = self.open_redirect("stderr", path, |p| p.create_file_anyhow())?;
+        self.cmd.stderr(f);
+        self.setup.stderr = Some(format!("2> {}", path_debug(path)));
+        Ok(self)
+    }
+
+    /// Redirect both stdout and stderr to append to the file at `path`, providing the path as error context
+    pub fn append_output_to_path_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let out = self.open_redirect("output", path, |p| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(p)
+                .with_path_context(p)
+        })?;
+        let err = out
+            .try_clone()
+            .with_path_context(path)
+            .with_context(|| format!("while redirecting output of {}", self.anyhow_context()))?;
+        self.cmd.stdout(out).stderr(err);
+        self.setup.stdout = Some(format!(">> {}", path_debug(path)));
+        self.setup.stderr = Some(format!("2>> {}", path_debug(path)));
+        Ok(self)
+    }
+
+    /// Redirect stdin from the file at `path`, providing the path as error context
+    pub fn stdin_from_path_anyhow<P>(&mut self, path: P) -> anyhow::Result<&mut Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = self.open_redirect("stdin", path, |p| p.open_file_anyhow())?;
+        self.cmd.stdin(f);
+        self.setup.stdin = Some(format!("< {}", path_debug(path)));
+        Ok(self)
+    }
+
+    fn open_redirect<F>(&self, stream: &str, path: &Path, open: F) -> anyhow::Result<File>
+    where
+        F: FnOnce(&Path) -> anyhow::Result<File>,
+    {
+        open(path)
+            .with_context(|| format!("while redirecting {} of {}", stream, self.anyhow_context()))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn setup(&self) -> &Setup {
+        &self.setup
+    }
+
+    /// Override [CommandAnyhow::spawn_anyhow](crate::CommandAnyhow::spawn_anyhow) to describe the recorded setup
+    pub fn spawn_anyhow(&mut self) -> anyhow::Result<Child> {
+        command::spawn(&mut self.cmd, &self.setup)
+    }
+
+    /// Override [CommandAnyhow::output_anyhow](crate::CommandAnyhow::output_anyhow) to describe the recorded setup
+    pub fn output_anyhow(&mut self) -> anyhow::Result<Output> {
+        command::output(&mut self.cmd, &self.setup)
+    }
+
+    /// Override [CommandAnyhow::status_anyhow](crate::CommandAnyhow::status_anyhow) to describe the recorded setup
+    pub fn status_anyhow(&mut self) -> anyhow::Result<ExitStatus> {
+        command::status(&mut self.cmd, &self.setup)
+    }
+
+    /// Override [CommandAnyhow::exec_anyhow](crate::CommandAnyhow::exec_anyhow) to describe the recorded setup
+    #[cfg(target_family = "unix")]
+    pub fn exec_anyhow(&mut self) -> anyhow::Error {
+        command::exec(&mut self.cmd, &self.setup)
+    }
+
+    /// Override [CommandAnyhow::anyhow_context](crate::CommandAnyhow::anyhow_context) to describe the recorded setup
+    pub fn anyhow_context(&self) -> String {
+        command::describe(&self.cmd, &self.setup)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::process::{ExitStatus, Output};
+use crate::ResultAnyhow;
 use anyhow::Context;
 use std::ops::Deref;
 use std::process::{ChildStderr, ChildStdin, ChildStdout};
@@ -36,7 +37,10 @@ impl Deref for Child {
 impl Child {
     /// Override [std::process::Child::kill] with the command as error context
     pub fn kill(&mut self) -> anyhow::Result<()> {
-        self.child.kill().context(self.cmddesc.clone())
+        self.child
+            .kill()
+            .context(self.cmddesc.clone())
+            .with_scope_context()
     }
 
     /// Override [std::process::Child::wait] with the command as error context
@@ -45,6 +49,7 @@ impl Child {
             .wait()
             .map(|es| ExitStatus::from((es, self.cmddesc.clone())))
             .context(self.cmddesc.clone())
+            .with_scope_context()
     }
 
     /// Override [std::process::Child::try_wait] with the command as error context
@@ -53,6 +58,7 @@ impl Child {
             .try_wait()
             .map(|optes| optes.map(|es| ExitStatus::from((es, self.cmddesc.clone()))))
             .context(self.cmddesc.clone())
+            .with_scope_context()
     }
 
     /// Override [std::process::Child::wait_with_output] with the command as error context
@@ -61,5 +67,6 @@ impl Child {
             .wait_with_output()
             .map(|o| Output::wrap(o, self.cmddesc.clone()))
             .context(self.cmddesc)
+            .with_scope_context()
     }
 }
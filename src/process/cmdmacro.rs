@@ -0,0 +1,44 @@
+/// Build a [std::process::Command] from a program and arguments
+///
+/// Each argument may be any `AsRef<OsStr>` value, and an argument prefixed with `..` is an
+/// iterable whose items are all appended as arguments. Because the result is a plain
+/// [std::process::Command], the [CommandAnyhow](crate::CommandAnyhow) methods describe exactly
+/// the arguments given, up to the [DisplayPolicy](crate::display::DisplayPolicy) length limit:
+///
+/// ```
+/// use anyhow_std::{cmd, CommandAnyhow};
+/// use std::path::Path;
+///
+/// let dir = Path::new("/tmp");
+/// let extra = ["--oneline", "-n1"];
+/// let mut cmd = cmd!("/! no such program !/", "-C", dir, "log", ..extra);
+///
+/// assert_eq!(
+///     cmd.anyhow_context(),
+///     r#"command: "/! no such program !/" "-C" "/tmp" "log" "--oneline" "-n1""#,
+/// );
+/// assert!(cmd.status_anyhow().is_err());
+/// ```
+#[macro_export]
+macro_rules! cmd {
+    ( @args $cmd:ident ) => {};
+
+    ( @args $cmd:ident , ) => {};
+
+    ( @args $cmd:ident , .. $splat:expr $( , $( $rest:tt )* )? ) => {
+        $cmd.args($splat);
+        $crate::cmd!( @args $cmd $( , $( $rest )* )? );
+    };
+
+    ( @args $cmd:ident , $arg:expr $( , $( $rest:tt )* )? ) => {
+        $cmd.arg($arg);
+        $crate::cmd!( @args $cmd $( , $( $rest )* )? );
+    };
+
+    ( $program:expr $( , $( $args:tt )* )? ) => {{
+        #[allow(unused_mut)]
+        let mut cmd = ::std::process::Command::new($program);
+        $crate::cmd!( @args cmd $( , $( $args )* )? );
+        cmd
+    }};
+}
@@ -1,8 +1,15 @@
+use crate::context::attach_scopes;
+use crate::display::{command_debug, path_debug};
+use crate::error::CommandContext;
 use crate::process::{Child, ExitStatus, Output};
-use anyhow::Context;
+use std::ffi::OsString;
+use std::path::PathBuf;
 use std::process::Command;
 
 /// Extend [std::process::Command] with [anyhow] methods
+///
+/// When a program cannot be found, errors additionally diagnose the likely cause, such as a
+/// missing current directory or the `PATH` directories which were searched.
 pub trait CommandAnyhow {
     /// Wrap [Command::spawn](std::process::Command::spawn), providing the command as error context
     fn spawn_anyhow(&mut self) -> anyhow::Result<Child>;
@@ -13,30 +20,214 @@ pub trait CommandAnyhow {
     /// Wrap [Command::status](std::process::Command::status), providing the command as error context
     fn status_anyhow(&mut self) -> anyhow::Result<ExitStatus>;
 
-    /// Describe the command for error contexts
+    /// Wrap [CommandExt::exec](std::os::unix::process::CommandExt::exec), providing the command as error context
+    ///
+    /// Like `exec`, this only returns if replacing the current process failed.
+    #[cfg(target_family = "unix")]
+    fn exec_anyhow(&mut self) -> anyhow::Error;
+
+    /// Wrap [CommandExt::pre_exec](std::os::unix::process::CommandExt::pre_exec) with a hook returning [anyhow::Result]
+    ///
+    /// The hook runs after `fork`, so its error is neither formatted nor dropped there, as
+    /// neither is async-signal-safe. [CommandAnyhow::exec_anyhow] reports the hook's error as a
+    /// `pre_exec hook` error, whereas a spawned child can only report `EINVAL` back to the
+    /// parent. Register hooks with
+    /// [CommandBuilder::pre_exec_anyhow](crate::process::CommandBuilder::pre_exec_anyhow) to have
+    /// such errors diagnosed as a failed hook.
+    ///
+    /// # Safety
+    ///
+    /// This has the same safety requirements as [CommandExt::pre_exec](std::os::unix::process::CommandExt::pre_exec).
+    #[cfg(target_family = "unix")]
+    unsafe fn pre_exec_anyhow<F>(&mut self, f: F) -> &mut Command
+    where
+        F: FnMut() -> anyhow::Result<()> + Send + Sync + 'static;
+
+    /// Describe the command for error contexts, truncated according to [DisplayPolicy::current](crate::display::DisplayPolicy::current)
     fn anyhow_context(&self) -> String;
 }
 
 impl CommandAnyhow for Command {
     fn spawn_anyhow(&mut self) -> anyhow::Result<Child> {
-        self.spawn()
-            .map(|c| Child::from((c, self.anyhow_context())))
-            .context(self.anyhow_context())
+        spawn(self, &Setup::default())
     }
 
     fn output_anyhow(&mut self) -> anyhow::Result<Output> {
-        self.output()
-            .map(|o| Output::wrap(o, self.anyhow_context()))
-            .context(self.anyhow_context())
+        output(self, &Setup::default())
     }
 
     fn status_anyhow(&mut self) -> anyhow::Result<ExitStatus> {
-        self.status()
-            .map(|c| ExitStatus::from((c, self.anyhow_context())))
-            .context(self.anyhow_context())
+        status(self, &Setup::default())
+    }
+
+    #[cfg(target_family = "unix")]
+    fn exec_anyhow(&mut self) -> anyhow::Error {
+        exec(self, &Setup::default())
+    }
+
+    #[cfg(target_family = "unix")]
+    unsafe fn pre_exec_anyhow<F>(&mut self, mut f: F) -> &mut Command
+    where
+        F: FnMut() -> anyhow::Result<()> + Send + Sync + 'static,
+    {
+        use std::os::unix::process::CommandExt;
+
+        self.pre_exec(move || {
+            f().map_err(|e| {
+                // Neither formatting nor dropping `e` is async-signal-safe, so it is leaked or
+                // stored for the parent without allocating:
+                match PRE_EXEC_FAILURE.try_lock() {
+                    Ok(mut failure) => std::mem::forget(failure.replace(e)),
+                    Err(_) => std::mem::forget(e),
+                }
+                std::io::ErrorKind::Other.into()
+            })
+        })
     }
 
     fn anyhow_context(&self) -> String {
-        format!("command: {:?}", self)
+        describe(self, &Setup::default())
+    }
+}
+
+/// The error of a failed [CommandAnyhow::pre_exec_anyhow] hook
+///
+/// Hooks run after `fork`, so they store their error here rather than formatting it. Only
+/// [CommandAnyhow::exec_anyhow] runs hooks in the calling process, so it is the only reader; a
+/// spawned child's copy is discarded when the child exits.
+#[cfg(target_family = "unix")]
+static PRE_EXEC_FAILURE: std::sync::Mutex<Option<anyhow::Error>> = std::sync::Mutex::new(None);
+
+/// Setup applied to a [Command] which it does not expose, recorded by [CommandBuilder](crate::process::CommandBuilder)
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Setup {
+    /// Descriptions of privileged setup, such as `uid 1000`
+    pub(crate) privileged: Vec<String>,
+    /// Stdio redirections in shell syntax, such as `> "out.log"`
+    pub(crate) stdin: Option<String>,
+    pub(crate) stdout: Option<String>,
+    pub(crate) stderr: Option<String>,
+    /// The number of hooks registered with `pre_exec_anyhow`
+    pub(crate) pre_exec_hooks: usize,
+}
+
+pub(crate) fn spawn(cmd: &mut Command, setup: &Setup) -> anyhow::Result<Child> {
+    cmd.spawn()
+        .map(|c| Child::from((c, describe(cmd, setup))))
+        .map_err(|e| failure(cmd, setup, e))
+}
+
+pub(crate) fn output(cmd: &mut Command, setup: &Setup) -> anyhow::Result<Output> {
+    cmd.output()
+        .map(|o| Output::wrap(o, describe(cmd, setup)))
+        .map_err(|e| failure(cmd, setup, e))
+}
+
+pub(crate) fn status(cmd: &mut Command, setup: &Setup) -> anyhow::Result<ExitStatus> {
+    cmd.status()
+        .map(|es| ExitStatus::from((es, describe(cmd, setup))))
+        .map_err(|e| failure(cmd, setup, e))
+}
+
+#[cfg(target_family = "unix")]
+pub(crate) fn exec(cmd: &mut Command, setup: &Setup) -> anyhow::Error {
+    use std::os::unix::process::CommandExt;
+
+    let error = cmd.exec();
+    let hook_failure =
+        if error.kind() == std::io::ErrorKind::Other && error.raw_os_error().is_none() {
+            PRE_EXEC_FAILURE
+                .lock()
+                .ok()
+                .and_then(|mut failure| failure.take())
+        } else {
+            None
+        };
+    let error = match hook_failure {
+        Some(e) => e.context("pre_exec hook"),
+        None => diagnose(cmd, setup, error),
+    };
+    attach_scopes(CommandContext::attach(error, cmd, describe(cmd, setup)))
+}
+
+pub(crate) fn describe(cmd: &Command, setup: &Setup) -> String {
+    let mut desc = format!("command: {}", command_debug(cmd));
+    let redirects = [&setup.stdin, &setup.stdout, &setup.stderr];
+    for redirect in redirects.into_iter().flatten() {
+        desc.push(' ');
+        desc.push_str(redirect);
     }
+    desc
+}
+
+fn failure(cmd: &Command, setup: &Setup, error: std::io::Error) -> anyhow::Error {
+    attach_scopes(CommandContext::attach(
+        diagnose(cmd, setup, error),
+        cmd,
+        describe(cmd, setup),
+    ))
+}
+
+/// Add a description of the likely cause of `error` when one can be determined
+fn diagnose(cmd: &Command, setup: &Setup, error: std::io::Error) -> anyhow::Error {
+    match diagnosis(cmd, setup, &error) {
+        Some(desc) => anyhow::Error::from(error).context(desc),
+        None => anyhow::Error::from(error),
+    }
+}
+
+pub(super) fn diagnosis(cmd: &Command, setup: &Setup, error: &std::io::Error) -> Option<String> {
+    use std::io::ErrorKind::NotFound;
+
+    #[cfg(target_family = "unix")]
+    if error.raw_os_error() == Some(1) && !setup.privileged.is_empty() {
+        return Some(format!(
+            "setting {} may require privileges",
+            setup.privileged.join(", "),
+        ));
+    }
+
+    // A spawned child reports a failed hook as EINVAL, having no other way to describe it:
+    #[cfg(target_family = "unix")]
+    if error.raw_os_error() == Some(22) && setup.pre_exec_hooks > 0 {
+        return Some("pre_exec hook failed".to_string());
+    }
+
+    if error.kind() != NotFound {
+        return None;
+    }
+
+    if let Some(dir) = cmd.get_current_dir() {
+        if !dir.is_dir() {
+            return Some(format!(
+                "current directory {} does not exist",
+                path_debug(dir)
+            ));
+        }
+    }
+
+    let program = cmd.get_program();
+    if PathBuf::from(program).components().count() != 1 {
+        // Programs with a directory component are not searched for in PATH.
+        return None;
+    }
+
+    match command_path_var(cmd) {
+        None => Some(format!(
+            "program {:?} not found because PATH is not set",
+            program.to_string_lossy()
+        )),
+        // When the program exists the cause lies elsewhere, such as a missing interpreter:
+        Some(pathvar) => crate::env::which_in(program, &pathvar)
+            .err()
+            .map(|e| e.to_string()),
+    }
+}
+
+/// The `PATH` the child would search, accounting for overrides with [Command::env]
+fn command_path_var(cmd: &Command) -> Option<OsString> {
+    cmd.get_envs()
+        .find(|(k, _)| *k == "PATH")
+        .map(|(_, v)| v.map(OsString::from))
+        .unwrap_or_else(|| std::env::var_os("PATH"))
 }
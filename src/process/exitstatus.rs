@@ -1,3 +1,4 @@
+use crate::ResultAnyhow;
 use std::ops::Deref;
 
 /// Wrap [std::process::ExitStatus] to provide the command in error contexts
@@ -27,6 +28,7 @@ impl ExitStatus {
                     .map(|i| i.to_string())
                     .unwrap_or_else(|| "n/a".to_string())
             )))
+            .with_scope_context()
         }
     }
 
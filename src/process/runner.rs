@@ -0,0 +1,169 @@
+use crate::process::{Child, CommandAnyhow, ExitStatus, Output};
+use crate::ResultAnyhow;
+use std::ffi::OsString;
+use std::process::Command;
+
+/// Abstract over executing a [Command], so code that shells out can be tested without running programs
+///
+/// Production code uses [SystemRunner], while tests use [FakeRunner] to script results. Runners
+/// take the [Command] itself, so setup recorded by a
+/// [CommandBuilder](crate::process::CommandBuilder), such as stdio redirections, is not
+/// described in their error contexts.
+pub trait CommandRunner {
+    /// Spawn `cmd` as with [CommandAnyhow::spawn_anyhow]
+    fn spawn(&mut self, cmd: &mut Command) -> anyhow::Result<Child>;
+
+    /// Run `cmd` collecting its output as with [CommandAnyhow::output_anyhow]
+    fn output(&mut self, cmd: &mut Command) -> anyhow::Result<Output>;
+
+    /// Run `cmd` collecting its status as with [CommandAnyhow::status_anyhow]
+    fn status(&mut self, cmd: &mut Command) -> anyhow::Result<ExitStatus>;
+}
+
+/// A [CommandRunner] which executes commands by delegating to [CommandAnyhow]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn spawn(&mut self, cmd: &mut Command) -> anyhow::Result<Child> {
+        cmd.spawn_anyhow()
+    }
+
+    fn output(&mut self, cmd: &mut Command) -> anyhow::Result<Output> {
+        cmd.output_anyhow()
+    }
+
+    fn status(&mut self, cmd: &mut Command) -> anyhow::Result<ExitStatus> {
+        cmd.status_anyhow()
+    }
+}
+
+/// A [CommandRunner] which returns scripted results and records every invocation
+///
+/// Responses are matched on the exact program and arguments of a command. The returned
+/// [Output] and [ExitStatus] values carry the command description produced by
+/// [CommandAnyhow::anyhow_context], just as with [SystemRunner]. Invoking a command without a
+/// scripted response is an error.
+///
+/// [FakeRunner] cannot produce a [Child], so [CommandRunner::spawn] always fails after
+/// recording the invocation. On unix, [CommandRunner::output] and [CommandRunner::status] fail
+/// for exit codes outside `0..=255`, which a process cannot exit with, and on platforms other
+/// than unix and windows an exit status cannot be constructed from a code at all.
+#[derive(Debug, Default)]
+pub struct FakeRunner {
+    responses: Vec<(Vec<OsString>, FakeResponse)>,
+    invocations: Vec<Vec<OsString>>,
+}
+
+#[derive(Clone, Debug)]
+struct FakeResponse {
+    code: i32,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl FakeRunner {
+    /// Construct a [FakeRunner] with no scripted responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Script the exit `code`, `stdout`, and `stderr` to return when `cmdline` is run
+    ///
+    /// The first element of `cmdline` is the program, and the rest are its arguments.
+    pub fn respond<I, S, O, E>(&mut self, cmdline: I, code: i32, stdout: O, stderr: E) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+        O: Into<Vec<u8>>,
+        E: Into<Vec<u8>>,
+    {
+        self.responses.push((
+            cmdline.into_iter().map(Into::into).collect(),
+            FakeResponse {
+                code,
+                stdout: stdout.into(),
+                stderr: stderr.into(),
+            },
+        ));
+        self
+    }
+
+    /// The program and arguments of every command run so far, in order
+    pub fn invocations(&self) -> &[Vec<OsString>] {
+        &self.invocations
+    }
+
+    fn record(&mut self, cmd: &Command) -> anyhow::Result<FakeResponse> {
+        let cmdline = command_line(cmd);
+        let found = self
+            .responses
+            .iter()
+            .find(|(scripted, _)| scripted == &cmdline)
+            .map(|(_, response)| response.clone());
+        self.invocations.push(cmdline);
+        found
+            .ok_or_else(|| anyhow::Error::msg("no scripted response"))
+            .with_command_context(cmd)
+    }
+}
+
+impl CommandRunner for FakeRunner {
+    fn spawn(&mut self, cmd: &mut Command) -> anyhow::Result<Child> {
+        self.record(cmd)?;
+        Err(anyhow::anyhow!("FakeRunner cannot spawn processes")).with_command_context(cmd)
+    }
+
+    fn output(&mut self, cmd: &mut Command) -> anyhow::Result<Output> {
+        let response = self.record(cmd)?;
+        Ok(Output::wrap(
+            std::process::Output {
+                status: exit_status_from_code(response.code).with_command_context(cmd)?,
+                stdout: response.stdout,
+                stderr: response.stderr,
+            },
+            cmd.anyhow_context(),
+        ))
+    }
+
+    fn status(&mut self, cmd: &mut Command) -> anyhow::Result<ExitStatus> {
+        let response = self.record(cmd)?;
+        Ok(ExitStatus::from((
+            exit_status_from_code(response.code).with_command_context(cmd)?,
+            cmd.anyhow_context(),
+        )))
+    }
+}
+
+fn command_line(cmd: &Command) -> Vec<OsString> {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(OsString::from)
+        .collect()
+}
+
+#[cfg(target_family = "unix")]
+fn exit_status_from_code(code: i32) -> anyhow::Result<std::process::ExitStatus> {
+    use std::os::unix::process::ExitStatusExt;
+
+    if !(0..=255).contains(&code) {
+        anyhow::bail!("exit code {} is outside the range 0..=255", code);
+    }
+    // The raw wait status stores the exit code in the second byte:
+    Ok(std::process::ExitStatus::from_raw(code << 8))
+}
+
+#[cfg(target_family = "windows")]
+fn exit_status_from_code(code: i32) -> anyhow::Result<std::process::ExitStatus> {
+    use std::os::windows::process::ExitStatusExt;
+
+    Ok(std::process::ExitStatus::from_raw(code as u32))
+}
+
+#[cfg(not(any(target_family = "unix", target_family = "windows")))]
+fn exit_status_from_code(code: i32) -> anyhow::Result<std::process::ExitStatus> {
+    Err(anyhow::anyhow!(
+        "FakeRunner cannot construct an exit status for code {} on this platform",
+        code
+    ))
+}
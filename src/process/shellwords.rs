@@ -0,0 +1,80 @@
+use crate::ResultAnyhow;
+use anyhow::Context;
+use std::process::Command;
+
+/// Construct a [Command] from a command line string split into words with POSIX shell quoting
+///
+/// Words are separated by unquoted spaces, tabs, or newlines. Single quotes preserve their
+/// contents literally, double quotes allow backslash escapes of `$`, `` ` ``, `"`, and `\`, and
+/// an unquoted backslash escapes any character. No expansions, globs, or operators are
+/// interpreted, so characters such as `$` or `|` are passed through literally.
+///
+/// Errors provide the command line as context, and quoting errors give the 1-based column of
+/// the offending character.
+pub fn command_from_shell_str(cmdline: &str) -> anyhow::Result<Command> {
+    split_shell_words(cmdline)
+        .and_then(|words| {
+            let mut words = words.into_iter();
+            let program = words
+                .next()
+                .ok_or_else(|| anyhow::Error::msg("empty command line"))?;
+            let mut cmd = Command::new(program);
+            cmd.args(words);
+            Ok(cmd)
+        })
+        .with_context(|| format!("while parsing command line {:?}", cmdline))
+        .with_scope_context()
+}
+
+fn split_shell_words(s: &str) -> anyhow::Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = s.chars().enumerate();
+
+    while let Some((i, c)) = chars.next() {
+        let column = i + 1;
+        match c {
+            ' ' | '\t' | '\n' => words.extend(word.take()),
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => w.push(c),
+                        None => anyhow::bail!("unterminated single quote at column {}", column),
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '\n')) => {}
+                            Some((_, c @ ('$' | '`' | '"' | '\\'))) => w.push(c),
+                            Some((_, c)) => {
+                                w.push('\\');
+                                w.push(c);
+                            }
+                            None => {
+                                anyhow::bail!("unterminated double quote at column {}", column)
+                            }
+                        },
+                        Some((_, c)) => w.push(c),
+                        None => anyhow::bail!("unterminated double quote at column {}", column),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c)) => word.get_or_insert_with(String::new).push(c),
+                None => anyhow::bail!("trailing backslash at column {}", column),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    words.extend(word);
+    Ok(words)
+}
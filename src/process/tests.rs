@@ -1,4 +1,6 @@
 use crate::process::{CommandAnyhow, ExitStatus};
+use crate::testutils::{err_str, stringify_error};
+use std::ffi::OsString;
 use std::process::Command;
 use test_case::test_case;
 
@@ -37,3 +39,317 @@ fn exit_ok_error() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn fake_runner_scripted_output() -> anyhow::Result<()> {
+    use crate::process::{CommandRunner, FakeRunner};
+
+    let mut runner = FakeRunner::new();
+    runner.respond(["git", "status"], 0, "clean\n", "");
+    runner.respond(["git", "push"], 1, "", "rejected\n");
+
+    let output = runner.output(Command::new("git").arg("status"))?;
+    assert_eq!(output.stdout, b"clean\n");
+    output.status.exit_ok()?;
+
+    let status = runner.status(Command::new("git").arg("push"))?;
+    assert_eq!(status.code(), Some(1));
+
+    assert_eq!(
+        runner.invocations(),
+        &[
+            vec![OsString::from("git"), OsString::from("status")],
+            vec![OsString::from("git"), OsString::from("push")],
+        ],
+    );
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(256 ; "too large")]
+#[test_case(-1 ; "negative")]
+fn fake_runner_exit_code_out_of_range(code: i32) {
+    use crate::process::{CommandRunner, FakeRunner};
+
+    let mut runner = FakeRunner::new();
+    runner.respond(["git", "status"], code, "", "");
+    assert_eq!(
+        format!(
+            "{:#}",
+            runner
+                .status(Command::new("git").arg("status"))
+                .err()
+                .unwrap()
+        ),
+        format!(
+            r#"command: "git" "status": exit code {} is outside the range 0..=255"#,
+            code
+        ),
+    );
+    assert!(runner.output(Command::new("git").arg("status")).is_err());
+}
+
+#[test]
+fn fake_runner_unscripted() {
+    use crate::process::{CommandRunner, FakeRunner};
+
+    let mut runner = FakeRunner::new();
+    let r = runner.status(Command::new("git").arg("pull"));
+    assert_eq!(
+        format!("{:#}", r.err().unwrap()),
+        r#"command: "git" "pull": no scripted response"#,
+    );
+    assert_eq!(runner.invocations().len(), 1);
+}
+
+#[test_case(crate::cmd!("git") => r#"command: "git""# ; "program only")]
+#[test_case(crate::cmd!("git", "log",) => r#"command: "git" "log""# ; "trailing comma")]
+#[test_case(
+    crate::cmd!("git", "-C", std::path::Path::new("/tmp"), "log", ..["-n", "1"])
+    => r#"command: "git" "-C" "/tmp" "log" "-n" "1""#
+    ; "path and splat"
+)]
+#[test_case(
+    crate::cmd!("git", ..Vec::<String>::new(), OsString::from("status"))
+    => r#"command: "git" "status""#
+    ; "empty splat"
+)]
+fn cmd_macro(cmd: Command) -> String {
+    cmd.anyhow_context()
+}
+
+#[test_case("cc -O2 'my file.c'" => Ok(r#"command: "cc" "-O2" "my file.c""#.to_string()) ; "single quotes")]
+#[test_case(
+    r#"  echo "a \"b\" \$c \d"  x\ y '' "#
+    => Ok(r#"command: "echo" "a \"b\" $c \\d" "x y" """#.to_string())
+    ; "escapes and empty word"
+)]
+#[test_case("printf a\\\nb" => Ok(r#"command: "printf" "ab""#.to_string()) ; "line continuation")]
+#[test_case(
+    "   "
+    => err_str(r#"while parsing command line "   ": empty command line"#)
+    ; "empty"
+)]
+#[test_case(
+    "cc 'my file.c"
+    => err_str(r#"while parsing command line "cc 'my file.c": unterminated single quote at column 4"#)
+    ; "unterminated single"
+)]
+#[test_case(
+    r#"echo "abc \""#
+    => err_str(r#"while parsing command line "echo \"abc \\\"": unterminated double quote at column 6"#)
+    ; "unterminated double"
+)]
+#[test_case(
+    "echo abc\\"
+    => err_str(r#"while parsing command line "echo abc\\": trailing backslash at column 9"#)
+    ; "trailing backslash"
+)]
+fn command_from_shell_str(cmdline: &str) -> Result<String, String> {
+    stringify_error(crate::process::command_from_shell_str(cmdline).map(|c| c.anyhow_context()))
+}
+
+#[test_case(Command::spawn_anyhow)]
+#[test_case(Command::output_anyhow)]
+#[test_case(Command::status_anyhow)]
+fn unknown_program_in_path<F, T>(cb: F)
+where
+    F: FnOnce(&mut Command) -> anyhow::Result<T>,
+{
+    let mut cmd = Command::new("!-we-assume-this-program-does-not-exist-!");
+    cmd.env(
+        "PATH",
+        "/this/path/should/not/exist:/this/path/also/should/not/exist",
+    );
+
+    let r = cb(&mut cmd);
+    assert_eq!(
+        format!("{:#}", r.err().unwrap()),
+        // BUG: Platform specific error message:
+        "command: PATH=\"/this/path/should/not/exist:/this/path/also/should/not/exist\" \"!-we-assume-this-program-does-not-exist-!\": program \"!-we-assume-this-program-does-not-exist-!\" not found in PATH directories [\"/this/path/should/not/exist\", \"/this/path/also/should/not/exist\"]: No such file or directory (os error 2)",
+    );
+}
+
+#[test]
+fn missing_current_dir() {
+    let mut cmd = Command::new("true");
+    cmd.current_dir("/this/path/should/not/exist");
+
+    assert_eq!(
+        format!("{:#}", cmd.status_anyhow().err().unwrap()),
+        // BUG: Platform specific error message:
+        r#"command: cd "/this/path/should/not/exist" && "true": current directory "/this/path/should/not/exist" does not exist: No such file or directory (os error 2)"#,
+    );
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn exec_unknown_program() {
+    let mut cmd = Command::new("!-we-assume-this-program-does-not-exist-!");
+    cmd.env_remove("PATH");
+
+    assert_eq!(
+        format!("{:#}", cmd.exec_anyhow()),
+        // BUG: Platform specific error message:
+        r#"command: env -u PATH "!-we-assume-this-program-does-not-exist-!": program "!-we-assume-this-program-does-not-exist-!" not found because PATH is not set: No such file or directory (os error 2)"#,
+    );
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn exec_pre_exec_hook_error() {
+    let mut cmd = Command::new("true");
+    unsafe {
+        cmd.pre_exec_anyhow(|| Err(anyhow::anyhow!("setup failed").context("while preparing")));
+    }
+
+    assert_eq!(
+        format!("{:#}", cmd.exec_anyhow()),
+        r#"command: "true": pre_exec hook: while preparing: setup failed"#,
+    );
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn spawn_pre_exec_hook_error() {
+    let mut cmd = crate::process::CommandBuilder::new("true");
+    unsafe {
+        cmd.pre_exec_anyhow(|| Err(anyhow::anyhow!("setup failed")));
+    }
+
+    assert_eq!(
+        format!("{:#}", cmd.spawn_anyhow().err().unwrap()),
+        r#"command: "true": pre_exec hook failed: Invalid argument (os error 22)"#,
+    );
+    assert_eq!(
+        format!("{:#}", cmd.status_anyhow().err().unwrap()),
+        r#"command: "true": pre_exec hook failed: Invalid argument (os error 22)"#,
+    );
+}
+
+#[test]
+fn redirect_output_to_paths() -> anyhow::Result<()> {
+    use crate::process::CommandBuilder;
+    use crate::PathAnyhow;
+
+    let dir = tempfile::TempDir::new()?;
+    let input = dir.path().join("input");
+    let log = dir.path().join("log");
+    input.write_anyhow("hello")?;
+    log.write_anyhow("previous\n")?;
+
+    CommandBuilder::new("cat")
+        .stdin_from_path_anyhow(&input)?
+        .append_output_to_path_anyhow(&log)?
+        .status_anyhow()?
+        .exit_ok()?;
+    assert_eq!(log.read_to_string_anyhow()?, "previous\nhello");
+
+    CommandBuilder::new("cat")
+        .stdin_from_path_anyhow(&input)?
+        .stdout_to_path_anyhow(&log)?
+        .status_anyhow()?
+        .exit_ok()?;
+    assert_eq!(log.read_to_string_anyhow()?, "hello");
+    Ok(())
+}
+
+#[test]
+fn redirects_in_context() -> anyhow::Result<()> {
+    use crate::process::CommandBuilder;
+
+    let dir = tempfile::TempDir::new()?;
+    let log = dir.path().join("log");
+    let err = dir.path().join("err");
+
+    let mut cmd = CommandBuilder::new("/! we assume this program does not exist !/");
+    cmd.append_output_to_path_anyhow(&log)?
+        .stderr_to_path_anyhow(&err)?
+        .stdin_from_path_anyhow("/dev/null")?;
+
+    assert_eq!(
+        format!("{:#}", cmd.status_anyhow().err().unwrap()),
+        // BUG: Platform specific error message:
+        format!(
+            r#"command: "/! we assume this program does not exist !/" < "/dev/null" >> {:?} 2> {:?}: No such file or directory (os error 2)"#,
+            log, err,
+        ),
+    );
+    Ok(())
+}
+
+#[test]
+fn replaced_redirects_forgotten() -> anyhow::Result<()> {
+    use crate::process::CommandBuilder;
+    use std::process::Stdio;
+
+    let dir = tempfile::TempDir::new()?;
+    let log = dir.path().join("log");
+
+    let mut cmd = CommandBuilder::new("true");
+    cmd.append_output_to_path_anyhow(&log)?
+        .stdin_from_path_anyhow("/dev/null")?;
+    cmd.stdout(Stdio::null()).stdin(Stdio::null());
+
+    assert_eq!(
+        cmd.anyhow_context(),
+        format!(r#"command: "true" 2>> {:?}"#, log)
+    );
+    cmd.stderr(Stdio::null()).status_anyhow()?.exit_ok()?;
+    assert_eq!(cmd.anyhow_context(), r#"command: "true""#);
+    Ok(())
+}
+
+#[test_case(crate::process::CommandBuilder::stdin_from_path_anyhow, "stdin")]
+#[test_case(crate::process::CommandBuilder::stdout_to_path_anyhow, "stdout")]
+#[test_case(crate::process::CommandBuilder::stderr_to_path_anyhow, "stderr")]
+#[test_case(crate::process::CommandBuilder::append_output_to_path_anyhow, "output")]
+fn redirect_missing_path<F>(redirect: F, stream: &str)
+where
+    F: for<'a> FnOnce(
+        &'a mut crate::process::CommandBuilder,
+        &'static str,
+    ) -> anyhow::Result<&'a mut crate::process::CommandBuilder>,
+{
+    let mut cmd = crate::process::CommandBuilder::new("cat");
+    let r = redirect(&mut cmd, "/this/path/should/not/exist/file");
+    assert_eq!(
+        format!("{:#}", r.err().unwrap()),
+        // BUG: Platform specific error message:
+        format!(
+            r#"while redirecting {} of command: "cat": while processing path "/this/path/should/not/exist/file": No such file or directory (os error 2)"#,
+            stream,
+        ),
+    );
+}
+
+#[cfg(target_family = "unix")]
+#[test_case(crate::process::CommandBuilder::new("true") => None ; "no privileged setup")]
+#[test_case(
+    { let mut b = crate::process::CommandBuilder::new("true"); b.uid(1000).process_group(0); b }
+    => Some("setting uid 1000, process group 0 may require privileges".to_string())
+    ; "uid and process group"
+)]
+fn permission_denied_diagnosis(builder: crate::process::CommandBuilder) -> Option<String> {
+    use crate::process::command::{diagnosis, Setup};
+
+    let setup: &Setup = builder.setup();
+    diagnosis(&builder, setup, &std::io::Error::from_raw_os_error(1))
+}
+
+#[test]
+fn builder_describes_command() {
+    let mut builder =
+        crate::process::CommandBuilder::new("/! we assume this program does not exist !/");
+    builder.arg("ARG");
+
+    assert_eq!(
+        builder.anyhow_context(),
+        r#"command: "/! we assume this program does not exist !/" "ARG""#,
+    );
+    assert_eq!(
+        format!("{:#}", builder.status_anyhow().err().unwrap()),
+        // BUG: Platform specific error message:
+        r#"command: "/! we assume this program does not exist !/" "ARG": No such file or directory (os error 2)"#,
+    );
+}
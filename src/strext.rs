@@ -0,0 +1,84 @@
+use crate::display::truncate;
+use crate::AnyhowContext;
+use crate::ResultAnyhow;
+use anyhow::Context;
+use std::str::FromStr;
+
+/// Extend [str] with [anyhow] methods
+pub trait StrAnyhow {
+    /// Wrap [str::parse], providing the string, truncated if long, and the target type as error context
+    fn parse_anyhow<T>(&self) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static;
+
+    /// Wrap [str::split_once], or provide an error describing the string if `delimiter` is missing
+    fn split_once_anyhow(&self, delimiter: char) -> anyhow::Result<(&str, &str)>;
+
+    /// Wrap [str::strip_prefix], or provide an error describing the string if `prefix` is missing
+    fn strip_prefix_anyhow(&self, prefix: &str) -> anyhow::Result<&str>;
+
+    /// Wrap [str::strip_suffix], or provide an error describing the string if `suffix` is missing
+    fn strip_suffix_anyhow(&self, suffix: &str) -> anyhow::Result<&str>;
+
+    /// Produce the byte offset and `char` at the `index`th `char`, as given by [str::char_indices], or provide an error describing the string if it is too short
+    fn expect_char_anyhow(&self, index: usize) -> anyhow::Result<(usize, char)>;
+}
+
+impl StrAnyhow for str {
+    fn parse_anyhow<T>(&self) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.parse()
+            .with_context(|| parse_context::<T>(self))
+            .with_scope_context()
+    }
+
+    fn split_once_anyhow(&self, delimiter: char) -> anyhow::Result<(&str, &str)> {
+        self.split_once(delimiter)
+            .ok_or_else(|| anyhow::anyhow!("missing expected delimiter {:?}", delimiter))
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+
+    fn strip_prefix_anyhow(&self, prefix: &str) -> anyhow::Result<&str> {
+        self.strip_prefix(prefix)
+            .ok_or_else(|| anyhow::anyhow!("missing expected prefix {:?}", prefix))
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+
+    fn strip_suffix_anyhow(&self, suffix: &str) -> anyhow::Result<&str> {
+        self.strip_suffix(suffix)
+            .ok_or_else(|| anyhow::anyhow!("missing expected suffix {:?}", suffix))
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+
+    fn expect_char_anyhow(&self, index: usize) -> anyhow::Result<(usize, char)> {
+        self.char_indices()
+            .nth(index)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "expected a char at index {}, but there are only {}",
+                    index,
+                    self.chars().count()
+                )
+            })
+            .with_context(|| self.anyhow_context())
+            .with_scope_context()
+    }
+}
+
+pub(crate) fn parse_context<T>(s: &str) -> String {
+    format!(
+        "while parsing {:?} as {}",
+        truncate(s.into()),
+        std::any::type_name::<T>()
+    )
+}
+
+#[cfg(test)]
+mod tests;
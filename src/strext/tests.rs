@@ -0,0 +1,48 @@
+use crate::testutils::{err_str, stringify_error};
+use crate::StrAnyhow;
+use test_case::test_case;
+
+#[test_case("42" => Ok(42))]
+#[test_case("forty-two" => err_str(r#"while parsing "forty-two" as u16: invalid digit found in string"#))]
+#[test_case("70000" => err_str(r#"while parsing "70000" as u16: number too large to fit in target type"#))]
+fn parse(input: &str) -> Result<u16, String> {
+    stringify_error(input.parse_anyhow())
+}
+
+#[test]
+fn parse_long() {
+    let input = "9".repeat(100);
+    assert_eq!(
+        stringify_error(input.parse_anyhow::<u8>()),
+        err_str(&format!(
+            "while parsing \"{}\u{2772}\u{2026}\u{2773}{}\" as u8: number too large to fit in target type",
+            "9".repeat(40),
+            "9".repeat(37)
+        ))
+    );
+}
+
+#[test_case("key=value=more", '=' => Ok(("key", "value=more")))]
+#[test_case("key", '=' => err_str(r#"while processing string "key": missing expected delimiter '='"#))]
+fn split_once(input: &str, delimiter: char) -> Result<(&str, &str), String> {
+    stringify_error(input.split_once_anyhow(delimiter))
+}
+
+#[test_case("v1.2", "v" => Ok("1.2"))]
+#[test_case("1.2", "v" => err_str(r#"while processing string "1.2": missing expected prefix "v""#))]
+fn strip_prefix<'a>(input: &'a str, prefix: &str) -> Result<&'a str, String> {
+    stringify_error(input.strip_prefix_anyhow(prefix))
+}
+
+#[test_case("10ms", "ms" => Ok("10"))]
+#[test_case("10s", "ms" => err_str(r#"while processing string "10s": missing expected suffix "ms""#))]
+fn strip_suffix<'a>(input: &'a str, suffix: &str) -> Result<&'a str, String> {
+    stringify_error(input.strip_suffix_anyhow(suffix))
+}
+
+#[test_case("aé😀", 0 => Ok((0, 'a')))]
+#[test_case("aé😀", 2 => Ok((3, '😀')))]
+#[test_case("aé😀", 3 => err_str(r#"while processing string "aé😀": expected a char at index 3, but there are only 3"#))]
+fn expect_char(input: &str, index: usize) -> Result<(usize, char), String> {
+    stringify_error(input.expect_char_anyhow(index))
+}
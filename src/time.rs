@@ -0,0 +1,28 @@
+use crate::ResultAnyhow;
+use std::time::{Duration, SystemTime};
+
+/// Extend [std::time::SystemTime] with [anyhow] methods
+pub trait SystemTimeAnyhow {
+    /// Wrap [SystemTime::duration_since], describing how far `earlier` is after `self` on error
+    fn duration_since_anyhow(&self, earlier: SystemTime) -> anyhow::Result<Duration>;
+
+    /// Wrap [SystemTime::elapsed], describing how far `self` is in the future on error
+    fn elapsed_anyhow(&self) -> anyhow::Result<Duration>;
+}
+
+impl SystemTimeAnyhow for SystemTime {
+    fn duration_since_anyhow(&self, earlier: SystemTime) -> anyhow::Result<Duration> {
+        self.duration_since(earlier)
+            .map_err(|e| anyhow::anyhow!("time is {:?} before the earlier time", e.duration()))
+            .with_scope_context()
+    }
+
+    fn elapsed_anyhow(&self) -> anyhow::Result<Duration> {
+        self.elapsed()
+            .map_err(|e| anyhow::anyhow!("time is {:?} in the future", e.duration()))
+            .with_scope_context()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,20 @@
+use crate::testutils::{err_str, stringify_error};
+use crate::SystemTimeAnyhow;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use test_case::test_case;
+
+#[test_case(10, 4 => Ok(Duration::from_secs(6)))]
+#[test_case(4, 10 => err_str("time is 6s before the earlier time"))]
+fn duration_since(later: u64, earlier: u64) -> Result<Duration, String> {
+    let later = UNIX_EPOCH + Duration::from_secs(later);
+    let earlier = UNIX_EPOCH + Duration::from_secs(earlier);
+    stringify_error(later.duration_since_anyhow(earlier))
+}
+
+#[test]
+fn elapsed_in_future() {
+    let future = SystemTime::now() + Duration::from_secs(3600);
+    let desc = format!("{:#}", future.elapsed_anyhow().err().unwrap());
+    assert!(desc.starts_with("time is "), "{:?}", desc);
+    assert!(desc.ends_with(" in the future"), "{:?}", desc);
+}
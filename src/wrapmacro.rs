@@ -0,0 +1,138 @@
+/// Generate an extension method which wraps a method of `Self`, providing [anyhow] error context
+///
+/// This is how [PathAnyhow](crate::PathAnyhow) is implemented, and it can be used within the
+/// `impl` of any extension trait to give third-party types the same context conventions. Each
+/// invocation takes the method name, a callable wrapping the underlying method, optional
+/// arguments, and the successful return type:
+///
+/// - `wrap_method!(name, callable, Ret)` wraps a `Result<Ret, E>` returning method.
+/// - `wrap_method!(name, callable, AsRefPath: arg, Ret)` adds a generic `arg: impl AsRef<Path>`
+///   argument, described with `with {arg} {path}` context.
+/// - `wrap_method!(name, callable, Args: (arg: Type, ...), Ret)` adds arguments which are
+///   `Clone + Debug`, described with `with {arg} {value:?}` context.
+///
+/// Any of these may be followed by `None: "description"` to wrap an `Option<Ret>` returning
+/// method instead, and then by `Context: callable` to describe `self` with a custom callable
+/// rather than [AnyhowContext](crate::AnyhowContext). Errors are also given the frames of any
+/// active [scope](crate::context::scope):
+///
+/// ```
+/// use anyhow_std::{wrap_method, AnyhowContext};
+/// use std::collections::HashMap;
+///
+/// struct Registry {
+///     name: String,
+///     entries: HashMap<String, u32>,
+/// }
+///
+/// impl AnyhowContext for Registry {
+///     fn anyhow_context(&self) -> String {
+///         format!("while processing registry {:?}", self.name)
+///     }
+/// }
+///
+/// trait RegistryAnyhow {
+///     fn get_anyhow(&self, key: String) -> anyhow::Result<u32>;
+///     fn bump_anyhow(&self, key: String, amount: u32) -> anyhow::Result<u32>;
+/// }
+///
+/// impl RegistryAnyhow for Registry {
+///     wrap_method!(
+///         get_anyhow,
+///         |r: &Registry, key: String| r.entries.get(&key).copied(),
+///         Args: (key: String),
+///         u32,
+///         None: "missing expected entry"
+///     );
+///
+///     wrap_method!(
+///         bump_anyhow,
+///         |r: &Registry, key: String, amount: u32| r.entries[&key].checked_add(amount),
+///         Args: (key: String, amount: u32),
+///         u32,
+///         None: "overflow",
+///         Context: |r: &Registry| format!("while bumping {:?}", r.name)
+///     );
+/// }
+///
+/// let registry = Registry {
+///     name: "ports".to_string(),
+///     entries: HashMap::from([("http".to_string(), u32::MAX)]),
+/// };
+/// assert_eq!(
+///     format!("{:#}", registry.get_anyhow("ssh".to_string()).err().unwrap()),
+///     r#"while processing registry "ports": with key "ssh": missing expected entry"#,
+/// );
+/// assert_eq!(
+///     format!("{:#}", registry.bump_anyhow("http".to_string(), 1).err().unwrap()),
+///     r#"while bumping "ports": with key "http", amount 1: overflow"#,
+/// );
+/// ```
+#[macro_export]
+macro_rules! wrap_method {
+    ( @lift $res:expr ) => {
+        $res
+    };
+
+    ( @lift $res:expr, $errordesc:expr ) => {
+        $res.ok_or_else(|| $crate::__anyhow::Error::msg($errordesc))
+    };
+
+    ( @self_context $res:expr, $this:expr ) => {
+        $res.map_err(|e| $crate::AnyhowContext::attach_anyhow_context($this, e.into()))
+    };
+
+    ( @self_context $res:expr, $this:expr, $ctx:expr ) => {
+        $crate::__anyhow::Context::with_context($res, || ($ctx)($this))
+    };
+
+    (
+        $method:ident, $cb:expr, AsRefPath: $arg:ident, $ret:ty
+        $( , None: $errordesc:expr )? $( , Context: $ctx:expr )? $(,)?
+    ) => {
+        #[allow(clippy::redundant_closure_call)]
+        fn $method<Q>(&self, $arg: Q) -> $crate::__anyhow::Result<$ret>
+        where
+            Q: AsRef<::std::path::Path>,
+        {
+            let argref = $arg.as_ref();
+            let res = $crate::wrap_method!( @lift $cb(self, argref) $( , $errordesc )? );
+            let res = $crate::ResultAnyhow::with_path_arg_context(res, stringify!($arg), argref);
+            let res = $crate::wrap_method!( @self_context res, self $( , $ctx )? );
+            $crate::ResultAnyhow::with_scope_context(res)
+        }
+    };
+
+    (
+        $method:ident, $cb:expr, Args: ( $( $arg:ident : $argty:ty ),+ $(,)? ), $ret:ty
+        $( , None: $errordesc:expr )? $( , Context: $ctx:expr )? $(,)?
+    ) => {
+        #[allow(clippy::redundant_closure_call)]
+        fn $method(&self, $( $arg: $argty ),+) -> $crate::__anyhow::Result<$ret> {
+            let res = $crate::wrap_method!(
+                @lift $cb(self, $( ::std::clone::Clone::clone(&$arg) ),+) $( , $errordesc )?
+            );
+            let res = $crate::__anyhow::Context::with_context(res, || {
+                let args: &[String] = &[$( format!("{} {:?}", stringify!($arg), $arg) ),+];
+                format!("with {}", args.join(", "))
+            });
+            let res = $crate::wrap_method!( @self_context res, self $( , $ctx )? );
+            $crate::ResultAnyhow::with_scope_context(res)
+        }
+    };
+
+    (
+        $method:ident, $cb:expr, $ret:ty
+        $( , None: $errordesc:expr )? $( , Context: $ctx:expr )? $(,)?
+    ) => {
+        #[allow(clippy::redundant_closure_call)]
+        fn $method(&self) -> $crate::__anyhow::Result<$ret> {
+            let res = $crate::wrap_method!( @lift $cb(self) $( , $errordesc )? );
+            let res = $crate::wrap_method!( @self_context res, self $( , $ctx )? );
+            $crate::ResultAnyhow::with_scope_context(res)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests;
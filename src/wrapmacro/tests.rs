@@ -0,0 +1,97 @@
+use crate::testutils::{err_str, stringify_error};
+use crate::AnyhowContext;
+use std::path::Path;
+
+struct Shelf {
+    label: &'static str,
+    books: Vec<&'static str>,
+}
+
+impl AnyhowContext for Shelf {
+    fn anyhow_context(&self) -> String {
+        format!("while processing shelf {:?}", self.label)
+    }
+}
+
+trait ShelfAnyhow {
+    fn first_anyhow(&self) -> anyhow::Result<&'static str>;
+    fn nth_anyhow(&self, index: usize) -> anyhow::Result<&'static str>;
+    fn count_anyhow(&self) -> anyhow::Result<u8>;
+    fn catalog_anyhow<Q>(&self, catalog: Q) -> anyhow::Result<String>
+    where
+        Q: AsRef<Path>;
+}
+
+impl ShelfAnyhow for Shelf {
+    wrap_method!(
+        first_anyhow,
+        |s: &Shelf| s.books.first().copied(),
+        &'static str,
+        None: "empty shelf"
+    );
+
+    wrap_method!(
+        nth_anyhow,
+        |s: &Shelf, index| s.books.get(index).copied(),
+        Args: (index: usize),
+        &'static str,
+        None: "no such book",
+        Context: |s: &Shelf| format!("while browsing {}", s.label)
+    );
+
+    wrap_method!(count_anyhow, |s: &Shelf| u8::try_from(s.books.len()), u8);
+
+    wrap_method!(
+        catalog_anyhow,
+        |_: &Shelf, p: &Path| std::fs::read_to_string(p),
+        AsRefPath: catalog,
+        String
+    );
+}
+
+fn shelf(books: Vec<&'static str>) -> Shelf {
+    Shelf {
+        label: "fiction",
+        books,
+    }
+}
+
+#[test]
+fn option() {
+    assert_eq!(
+        stringify_error(shelf(vec!["Emma"]).first_anyhow()),
+        Ok("Emma")
+    );
+    assert_eq!(
+        stringify_error(shelf(vec![]).first_anyhow()),
+        err_str(r#"while processing shelf "fiction": empty shelf"#)
+    );
+}
+
+#[test]
+fn args_with_custom_context() {
+    assert_eq!(
+        stringify_error(shelf(vec!["Emma"]).nth_anyhow(3)),
+        err_str("while browsing fiction: with index 3: no such book")
+    );
+}
+
+#[test]
+fn result() {
+    assert_eq!(
+        stringify_error(shelf(vec!["Emma"; 300]).count_anyhow()),
+        err_str(
+            r#"while processing shelf "fiction": out of range integral type conversion attempted"#
+        )
+    );
+}
+
+#[test]
+fn as_ref_path() {
+    assert_eq!(
+        stringify_error(shelf(vec![]).catalog_anyhow("/this/path/should/not/exist")),
+        err_str(
+            r#"while processing shelf "fiction": with catalog "/this/path/should/not/exist": No such file or directory (os error 2)"#
+        )
+    );
+}